@@ -0,0 +1,359 @@
+// Appear and disappear animations for overlay elements.
+//
+// An element eases in with its appear effect, eases out with its disappear
+// effect over the fade time, and a combo can bounce when it is pressed again.
+// Poses are computed from the time since each event rather than advanced per
+// frame, so animations run at the same speed whatever the frame rate. Scaling
+// and sliding are applied as a visual transform and don't move the layout.
+
+use std::f32::consts::PI;
+
+use eframe::egui::{self, emath::TSTransform};
+
+// Seconds a bounce lasts, and how much it grows the element at its peak
+const BOUNCE_TIME: f32 = 0.35;
+const BOUNCE_SCALE: f32 = 0.2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AnimatedElement {
+    Combo,
+    Gesture,
+    Ticker,
+}
+
+impl AnimatedElement {
+    pub const ALL: [AnimatedElement; 3] = [
+        AnimatedElement::Combo,
+        AnimatedElement::Gesture,
+        AnimatedElement::Ticker,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AnimatedElement::Combo => "Combo",
+            AnimatedElement::Gesture => "Gesture",
+            AnimatedElement::Ticker => "Ticker",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Effect {
+    None,
+    Fade,
+    Pop,
+    Slide,
+}
+
+impl Effect {
+    pub const ALL: [Effect; 4] = [Effect::None, Effect::Fade, Effect::Pop, Effect::Slide];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Effect::None => "None",
+            Effect::Fade => "Fade",
+            Effect::Pop => "Pop",
+            Effect::Slide => "Slide",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Edge {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+impl Edge {
+    pub const ALL: [Edge; 4] = [Edge::Left, Edge::Right, Edge::Top, Edge::Bottom];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Edge::Left => "Left",
+            Edge::Right => "Right",
+            Edge::Top => "Top",
+            Edge::Bottom => "Bottom",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+    /// Overshoots the end and settles back.
+    Back,
+}
+
+impl Easing {
+    pub const ALL: [Easing; 5] = [
+        Easing::Linear,
+        Easing::EaseIn,
+        Easing::EaseOut,
+        Easing::EaseInOut,
+        Easing::Back,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Easing::Linear => "Linear",
+            Easing::EaseIn => "Ease in",
+            Easing::EaseOut => "Ease out",
+            Easing::EaseInOut => "Ease in and out",
+            Easing::Back => "Overshoot",
+        }
+    }
+
+    /// Maps linear progress `t` in 0..=1 to eased progress, which starts at 0
+    /// and ends at 1 but may leave that range in between.
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+            Easing::Back => {
+                // The usual ease-out-back constant, about a 10% overshoot
+                let c1 = 1.70158;
+                let c3 = c1 + 1.0;
+                1.0 + c3 * (t - 1.0).powi(3) + c1 * (t - 1.0).powi(2)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Animation {
+    pub appear: Effect,
+    pub disappear: Effect,
+    /// Edge that sliding elements come in from and leave towards.
+    pub edge: Edge,
+    pub easing: Easing,
+    /// Seconds the appear effect takes; disappearing takes the fade time.
+    pub duration: f32,
+    /// Bounce when a combo is pressed again.
+    pub is_bounce: bool,
+}
+
+impl Default for Animation {
+    // Appear at once and fade out linearly
+    fn default() -> Self {
+        Self {
+            appear: Effect::None,
+            disappear: Effect::Fade,
+            edge: Edge::Left,
+            easing: Easing::Linear,
+            duration: 0.25,
+            is_bounce: false,
+        }
+    }
+}
+
+/// How an element looks at one moment.
+pub struct Pose {
+    pub opacity: f32,
+    pub transform: TSTransform,
+}
+
+impl Pose {
+    /// Alpha for drawing code that takes one, 0 once the element is gone.
+    pub fn alpha(&self) -> u8 {
+        (255.0 * self.opacity.clamp(0.0, 1.0)) as u8
+    }
+}
+
+impl Animation {
+    /// Pose of an element `shown` seconds after it appeared and `fade` of the
+    /// way through its fade out, 0 while it is fully shown. `bounced` is the
+    /// seconds since its last repeated press. Pops scale around `pivot`, and
+    /// slides travel the size of `area` so they start and end outside it.
+    pub fn pose(
+        &self,
+        shown: f32,
+        fade: f32,
+        bounced: Option<f32>,
+        pivot: egui::Pos2,
+        area: egui::Vec2,
+    ) -> Pose {
+        let mut opacity = 1.0;
+        let mut scale = 1.0;
+        let mut offset = egui::Vec2::ZERO;
+
+        // How far each effect has hidden the element, 1 being fully hidden
+        let appear = if self.duration > 0.0 {
+            shown / self.duration
+        } else {
+            1.0
+        };
+        let effects = [
+            (self.appear, 1.0 - self.easing.apply(appear)),
+            (self.disappear, self.easing.apply(fade)),
+        ];
+        for (effect, hidden) in effects {
+            match effect {
+                Effect::None => {}
+                Effect::Fade => opacity *= 1.0 - hidden,
+                Effect::Pop => scale *= (1.0 - hidden).max(0.0),
+                Effect::Slide => {
+                    offset += hidden
+                        * match self.edge {
+                            Edge::Left => egui::vec2(-area.x, 0.0),
+                            Edge::Right => egui::vec2(area.x, 0.0),
+                            Edge::Top => egui::vec2(0.0, -area.y),
+                            Edge::Bottom => egui::vec2(0.0, area.y),
+                        };
+                }
+            }
+        }
+        // Without a disappear effect the element goes as soon as the fade
+        // out starts
+        if fade >= 1.0 || (self.disappear == Effect::None && fade > 0.0) {
+            opacity = 0.0;
+        }
+
+        if let Some(bounced) = bounced.filter(|&b| self.is_bounce && b < BOUNCE_TIME) {
+            // Grows, then dips below its size once before settling
+            let t = bounced / BOUNCE_TIME;
+            scale *= 1.0 + BOUNCE_SCALE * (2.0 * PI * t).sin() * (1.0 - t);
+        }
+
+        let transform = TSTransform::from_translation(pivot.to_vec2() + offset)
+            * TSTransform::from_scaling(scale)
+            * TSTransform::from_translation(-pivot.to_vec2());
+        Pose { opacity, transform }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AREA: egui::Vec2 = egui::vec2(200.0, 40.0);
+
+    fn animation(appear: Effect, disappear: Effect) -> Animation {
+        Animation {
+            appear,
+            disappear,
+            duration: 0.5,
+            ..Default::default()
+        }
+    }
+
+    fn pose(animation: &Animation, shown: f32, fade: f32) -> Pose {
+        animation.pose(shown, fade, None, egui::Pos2::ZERO, AREA)
+    }
+
+    #[test]
+    fn easings_start_at_zero_and_end_at_one() {
+        for easing in Easing::ALL {
+            assert!(easing.apply(0.0).abs() < 1e-6, "{}", easing.label());
+            assert!((easing.apply(1.0) - 1.0).abs() < 1e-6, "{}", easing.label());
+            // Progress outside the animation is clamped
+            assert_eq!(easing.apply(-1.0), easing.apply(0.0));
+            assert_eq!(easing.apply(2.0), easing.apply(1.0));
+        }
+    }
+
+    #[test]
+    fn easings_shape_the_middle() {
+        assert_eq!(Easing::Linear.apply(0.25), 0.25);
+        assert_eq!(Easing::EaseIn.apply(0.5), 0.125);
+        assert_eq!(Easing::EaseOut.apply(0.5), 0.875);
+        assert_eq!(Easing::EaseInOut.apply(0.25), 0.0625);
+        assert_eq!(Easing::EaseInOut.apply(0.5), 0.5);
+        assert_eq!(Easing::EaseInOut.apply(0.75), 0.9375);
+        // Overshoots by about 10% before settling
+        let peak = (0..=100)
+            .map(|i| Easing::Back.apply(i as f32 / 100.0))
+            .fold(0.0, f32::max);
+        assert!(peak > 1.09 && peak < 1.11, "{peak}");
+    }
+
+    #[test]
+    fn fades_in_and_out_over_time() {
+        let fade = animation(Effect::Fade, Effect::Fade);
+        assert_eq!(pose(&fade, 0.0, 0.0).opacity, 0.0);
+        assert_eq!(pose(&fade, 0.25, 0.0).opacity, 0.5);
+        assert_eq!(pose(&fade, 0.5, 0.0).alpha(), 255);
+        assert_eq!(pose(&fade, 10.0, 0.25).opacity, 0.75);
+        assert_eq!(pose(&fade, 10.0, 1.0).alpha(), 0);
+        // Fading out while still appearing combines both
+        assert_eq!(pose(&fade, 0.25, 0.5).opacity, 0.25);
+    }
+
+    #[test]
+    fn without_effects_appears_at_once_and_vanishes_when_fading() {
+        let none = animation(Effect::None, Effect::None);
+        assert_eq!(pose(&none, 0.0, 0.0).opacity, 1.0);
+        assert_eq!(pose(&none, 0.0, 0.01).opacity, 0.0);
+
+        let instant = Animation {
+            duration: 0.0,
+            ..animation(Effect::Fade, Effect::Fade)
+        };
+        assert_eq!(pose(&instant, 0.0, 0.0).opacity, 1.0);
+    }
+
+    #[test]
+    fn pops_around_the_pivot() {
+        let pop = animation(Effect::Pop, Effect::Pop);
+        let pivot = egui::pos2(50.0, 10.0);
+        let half = pop.pose(0.25, 0.0, None, pivot, AREA);
+        assert_eq!(half.transform.scaling, 0.5);
+        assert_eq!(half.transform * pivot, pivot);
+        assert_eq!(pose(&pop, 10.0, 0.75).transform.scaling, 0.25);
+        assert_eq!(pose(&pop, 10.0, 0.0).opacity, 1.0);
+    }
+
+    #[test]
+    fn slides_from_and_to_the_edge() {
+        let mut slide = animation(Effect::Slide, Effect::Slide);
+        assert_eq!(
+            pose(&slide, 0.0, 0.0).transform.translation,
+            egui::vec2(-AREA.x, 0.0)
+        );
+        assert_eq!(
+            pose(&slide, 0.25, 0.0).transform.translation,
+            egui::vec2(-AREA.x / 2.0, 0.0)
+        );
+        assert_eq!(
+            pose(&slide, 0.5, 0.0).transform.translation,
+            egui::Vec2::ZERO
+        );
+
+        slide.edge = Edge::Bottom;
+        assert_eq!(
+            pose(&slide, 10.0, 0.5).transform.translation,
+            egui::vec2(0.0, AREA.y / 2.0)
+        );
+    }
+
+    #[test]
+    fn bounces_after_a_repeated_press() {
+        let mut bounce = animation(Effect::None, Effect::Fade);
+        let scaling = |animation: &Animation, bounced: f32| {
+            animation
+                .pose(10.0, 0.0, Some(bounced), egui::Pos2::ZERO, AREA)
+                .transform
+                .scaling
+        };
+        // Off unless enabled
+        assert_eq!(scaling(&bounce, BOUNCE_TIME / 4.0), 1.0);
+
+        bounce.is_bounce = true;
+        assert!(scaling(&bounce, BOUNCE_TIME / 4.0) > 1.1);
+        assert!(scaling(&bounce, BOUNCE_TIME * 0.75) < 1.0);
+        assert_eq!(scaling(&bounce, BOUNCE_TIME), 1.0);
+    }
+}
@@ -0,0 +1,302 @@
+// Combo segmentation: turns the raw key-down/key-up stream into the
+// combinations shown by the overlay.
+//
+// Rules:
+// - Pressing a non-modifier key that is not already held starts a new combo
+//   made of every held key plus the new one. Holding Ctrl and tapping A then B
+//   therefore shows "Ctrl + A" followed by "Ctrl + B".
+// - While the current combo holds only modifiers it is provisional: pressing
+//   another modifier extends it in place ("Ctrl" -> "Ctrl + Shift") and the
+//   next non-modifier key upgrades it in place ("Ctrl + Shift + P").
+// - Pressing a modifier after a complete combo, or after releasing part of a
+//   provisional one, starts a new provisional combo from the held keys.
+// - A key-down for a key that is already held is auto-repeat and leaves the
+//   combo unchanged.
+// - Releasing keys never changes the combo; it stays until the next press.
+// - Mouse buttons and wheel notches arrive as pseudo keys (see `key_names`)
+//   and follow the same rules, so "Ctrl + Click" is an ordinary combo. A held
+//   key can be replaced in place, which is how a click turns into a drag.
+
+use std::{collections::HashMap, time::Instant};
+
+use indexmap::IndexSet;
+
+use crate::shortcut::Modifier;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyAction {
+    Down,
+    Up,
+    /// The held key with this code turned into `vk`.
+    Replace(u32),
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct KeyEvent {
+    pub vk: u32,
+    /// Physical key from the hook as a set-1 scan code, with 0xE0 in the high
+    /// byte for extended keys. `None` for mouse pseudo keys.
+    pub scan_code: Option<u32>,
+    pub action: KeyAction,
+    pub time: Instant,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ComboChange {
+    /// A new combo replaced the previous one.
+    Started,
+    /// The current combo gained or replaced a key in place.
+    Extended,
+    /// A held key auto-repeated.
+    Repeated,
+}
+
+#[derive(Default)]
+pub struct ComboTracker {
+    held: IndexSet<u32>,
+    press_times: HashMap<u32, Instant>,
+    scan_codes: HashMap<u32, u32>,
+    current: IndexSet<u32>,
+    is_provisional: bool,
+}
+
+pub fn is_modifier(vk: u32) -> bool {
+    Modifier::from_vk(vk).is_some()
+}
+
+impl ComboTracker {
+    pub fn handle(&mut self, event: &KeyEvent) -> Option<ComboChange> {
+        match event.action {
+            KeyAction::Down => {
+                let change = self.key_down(event.vk);
+                if change != ComboChange::Repeated {
+                    self.press_times.insert(event.vk, event.time);
+                    if let Some(scan_code) = event.scan_code {
+                        self.scan_codes.insert(event.vk, scan_code);
+                    }
+                }
+                Some(change)
+            }
+            KeyAction::Up => {
+                self.key_up(event.vk);
+                None
+            }
+            KeyAction::Replace(from) => self.replace_key(from, event.vk),
+        }
+    }
+
+    pub fn key_down(&mut self, vk: u32) -> ComboChange {
+        if !self.held.insert(vk) {
+            return ComboChange::Repeated;
+        }
+
+        // Only extend while every key of the provisional combo is still held
+        let extends = self.is_provisional
+            && !self.current.is_empty()
+            && self.current.iter().all(|key| self.held.contains(key));
+
+        if extends {
+            self.current.insert(vk);
+        } else {
+            self.current = self.held.clone();
+        }
+        self.is_provisional = self.current.iter().all(|&key| is_modifier(key));
+
+        if extends {
+            ComboChange::Extended
+        } else {
+            ComboChange::Started
+        }
+    }
+
+    pub fn key_up(&mut self, vk: u32) {
+        self.held.shift_remove(&vk);
+        self.press_times.remove(&vk);
+        self.scan_codes.remove(&vk);
+    }
+
+    /// Swaps a held key for another, keeping its position, press time and scan
+    /// code.
+    pub fn replace_key(&mut self, from: u32, to: u32) -> Option<ComboChange> {
+        if !self.held.contains(&from) {
+            return None;
+        }
+
+        let swap = |keys: &IndexSet<u32>| {
+            keys.iter()
+                .map(|&key| if key == from { to } else { key })
+                .collect::<IndexSet<u32>>()
+        };
+        self.held = swap(&self.held);
+        if let Some(time) = self.press_times.remove(&from) {
+            self.press_times.insert(to, time);
+        }
+        if let Some(scan_code) = self.scan_codes.remove(&from) {
+            self.scan_codes.insert(to, scan_code);
+        }
+
+        if self.current.contains(&from) {
+            self.current = swap(&self.current);
+            Some(ComboChange::Extended)
+        } else {
+            None
+        }
+    }
+
+    /// When a held key went down, for keys fed through `handle`.
+    pub fn press_time(&self, vk: u32) -> Option<Instant> {
+        self.press_times.get(&vk).copied()
+    }
+
+    /// Physical key a held key was pressed with, for keys fed through
+    /// `handle`. Keys that share a virtual key, such as Home and numpad 7 with
+    /// NumLock off, are told apart by this.
+    pub fn scan_code(&self, vk: u32) -> Option<u32> {
+        self.scan_codes.get(&vk).copied()
+    }
+
+    /// Keys currently held down, in press order.
+    pub fn held(&self) -> &IndexSet<u32> {
+        &self.held
+    }
+
+    /// The combo currently on display.
+    pub fn current(&self) -> &IndexSet<u32> {
+        &self.current
+    }
+
+    /// True while the current combo holds only modifiers.
+    pub fn is_provisional(&self) -> bool {
+        self.is_provisional
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CTRL: u32 = 0xA2;
+    const SHIFT: u32 = 0xA0;
+    const A: u32 = 0x41;
+    const B: u32 = 0x42;
+    const P: u32 = 0x50;
+    const CLICK: u32 = 0x01;
+    const DRAG: u32 = 0x101;
+
+    fn event(vk: u32, action: KeyAction) -> KeyEvent {
+        KeyEvent {
+            vk,
+            scan_code: None,
+            action,
+            time: Instant::now(),
+        }
+    }
+
+    fn keys(vks: &[u32]) -> IndexSet<u32> {
+        vks.iter().copied().collect()
+    }
+
+    #[test]
+    fn each_key_under_a_held_modifier_starts_a_combo() {
+        let mut tracker = ComboTracker::default();
+        assert_eq!(tracker.key_down(CTRL), ComboChange::Started);
+        assert_eq!(tracker.key_down(A), ComboChange::Extended);
+        assert_eq!(tracker.current(), &keys(&[CTRL, A]));
+
+        tracker.key_up(A);
+        assert_eq!(tracker.current(), &keys(&[CTRL, A]));
+        assert_eq!(tracker.key_down(B), ComboChange::Started);
+        assert_eq!(tracker.current(), &keys(&[CTRL, B]));
+        assert!(!tracker.is_provisional());
+    }
+
+    #[test]
+    fn modifier_only_combo_is_extended_in_place() {
+        let mut tracker = ComboTracker::default();
+        tracker.key_down(CTRL);
+        assert!(tracker.is_provisional());
+        assert_eq!(tracker.key_down(SHIFT), ComboChange::Extended);
+        assert!(tracker.is_provisional());
+        assert_eq!(tracker.key_down(P), ComboChange::Extended);
+        assert_eq!(tracker.current(), &keys(&[CTRL, SHIFT, P]));
+        assert!(!tracker.is_provisional());
+
+        // A modifier after a complete combo starts over
+        tracker.key_up(SHIFT);
+        tracker.key_up(P);
+        assert_eq!(tracker.key_down(SHIFT), ComboChange::Started);
+        assert_eq!(tracker.current(), &keys(&[CTRL, SHIFT]));
+    }
+
+    #[test]
+    fn releasing_part_of_a_provisional_combo_starts_over() {
+        let mut tracker = ComboTracker::default();
+        tracker.key_down(CTRL);
+        tracker.key_down(SHIFT);
+        tracker.key_up(CTRL);
+        assert_eq!(tracker.current(), &keys(&[CTRL, SHIFT]));
+
+        assert_eq!(tracker.key_down(A), ComboChange::Started);
+        assert_eq!(tracker.current(), &keys(&[SHIFT, A]));
+    }
+
+    #[test]
+    fn auto_repeat_keeps_the_combo_and_press_time() {
+        let mut tracker = ComboTracker::default();
+        let press = event(A, KeyAction::Down);
+        tracker.handle(&event(CTRL, KeyAction::Down));
+        tracker.handle(&press);
+
+        let repeat = event(A, KeyAction::Down);
+        assert_eq!(tracker.handle(&repeat), Some(ComboChange::Repeated));
+        assert_eq!(tracker.current(), &keys(&[CTRL, A]));
+        assert_eq!(tracker.press_time(A), Some(press.time));
+
+        assert_eq!(tracker.handle(&event(A, KeyAction::Up)), None);
+        assert_eq!(tracker.press_time(A), None);
+        assert_eq!(tracker.held(), &keys(&[CTRL]));
+    }
+
+    #[test]
+    fn tracks_the_scan_code_of_each_held_key() {
+        let mut tracker = ComboTracker::default();
+        // Numpad 7 with NumLock off reports VK_HOME
+        let home = KeyEvent {
+            scan_code: Some(0x47),
+            ..event(0x24, KeyAction::Down)
+        };
+        tracker.handle(&home);
+        assert_eq!(tracker.scan_code(0x24), Some(0x47));
+
+        let repeat = KeyEvent {
+            scan_code: Some(0xE047),
+            ..home
+        };
+        tracker.handle(&repeat);
+        assert_eq!(tracker.scan_code(0x24), Some(0x47));
+
+        tracker.handle(&event(0x24, KeyAction::Up));
+        assert_eq!(tracker.scan_code(0x24), None);
+    }
+
+    #[test]
+    fn replace_turns_a_click_into_a_drag() {
+        let mut tracker = ComboTracker::default();
+        let click = event(CLICK, KeyAction::Down);
+        tracker.handle(&event(CTRL, KeyAction::Down));
+        tracker.handle(&click);
+
+        let drag = event(DRAG, KeyAction::Replace(CLICK));
+        assert_eq!(tracker.handle(&drag), Some(ComboChange::Extended));
+        assert_eq!(tracker.current(), &keys(&[CTRL, DRAG]));
+        assert_eq!(tracker.held(), &keys(&[CTRL, DRAG]));
+        assert_eq!(tracker.press_time(DRAG), Some(click.time));
+        assert_eq!(tracker.press_time(CLICK), None);
+
+        // Only held keys can be replaced
+        assert_eq!(
+            tracker.handle(&event(0x102, KeyAction::Replace(0x02))),
+            None
+        );
+    }
+}
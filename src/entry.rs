@@ -0,0 +1,407 @@
+// A displayed overlay entry: one combo, or a chord sequence such as
+// "Ctrl + K, Ctrl + C" when the first stroke is a configured prefix.
+// Pressing the same combo again while the entry is on screen bumps its press
+// counter ("Ctrl + Z ×8"); auto-repeat from a held key is counted separately.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    time::{Duration, Instant},
+};
+
+use indexmap::IndexSet;
+use windows::Win32::UI::Input::KeyboardAndMouse::HKL;
+
+use crate::{
+    combo::{ComboChange, ComboTracker},
+    key_hook::{self, key_combination_labels},
+    settings::{Lifetime, Settings},
+    shortcut::Shortcut,
+};
+
+/// Press and release time of one key within a stroke.
+#[derive(Clone, Copy, Debug)]
+pub struct KeyHold {
+    pub pressed: Instant,
+    pub released: Option<Instant>,
+}
+
+impl KeyHold {
+    pub fn duration(&self, now: Instant) -> Duration {
+        self.released.unwrap_or(now).duration_since(self.pressed)
+    }
+
+    pub fn is_hold(&self, now: Instant, threshold: Duration) -> bool {
+        self.duration(now) >= threshold
+    }
+}
+
+pub struct Stroke {
+    pub keys: IndexSet<u32>,
+    pub holds: HashMap<u32, KeyHold>,
+    /// Keyboard layout the stroke was typed with, so its label keeps the
+    /// characters it had after the user switches layouts.
+    pub layout: HKL,
+}
+
+impl Stroke {
+    /// Snapshot of the tracker's current combo with the keys' press times,
+    /// on the focused window's keyboard layout.
+    pub fn from_tracker(combo: &ComboTracker, time: Instant) -> Self {
+        let keys = combo.current().clone();
+        let holds = keys
+            .iter()
+            .map(|&vk| {
+                let hold = KeyHold {
+                    pressed: combo.press_time(vk).unwrap_or(time),
+                    released: None,
+                };
+                (vk, hold)
+            })
+            .collect();
+        Self {
+            keys,
+            holds,
+            layout: unsafe { key_hook::active_keyboard_layout() },
+        }
+    }
+}
+
+/// Joins the keys of one stroke.
+pub const KEY_SEPARATOR: &str = " + ";
+
+/// A piece of an entry's label: a key name, a separator or a counter.
+pub struct LabelSegment {
+    pub text: String,
+    pub hold: Option<KeyHold>,
+}
+
+impl LabelSegment {
+    fn text(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            hold: None,
+        }
+    }
+}
+
+pub struct ComboEntry {
+    pub strokes: Vec<Stroke>,
+    pub started: Instant,
+    pub updated: Instant,
+    /// Distinct presses of the same combo.
+    pub presses: u32,
+    /// Time of the latest of those presses.
+    pub pressed: Instant,
+    /// Auto-repeat key-downs while the combo was held.
+    pub repeats: u32,
+    /// Whether starting this entry moved the current entry into the history,
+    /// so that entry was still on screen when this one began.
+    displaced_current: bool,
+}
+
+impl ComboEntry {
+    pub fn new(stroke: Stroke, time: Instant) -> Self {
+        Self {
+            strokes: vec![stroke],
+            started: time,
+            updated: time,
+            presses: 1,
+            pressed: time,
+            repeats: 0,
+            displaced_current: false,
+        }
+    }
+
+    pub fn last_stroke(&self) -> &Stroke {
+        // Entries are never created without a stroke
+        self.strokes.last().unwrap()
+    }
+
+    /// Whether a stroke pressed at `time` belongs to this entry's sequence:
+    /// the previous stroke must be one of the configured prefixes and the new
+    /// stroke must arrive within the sequence timeout.
+    pub fn continues_sequence(&self, time: Instant, settings: &Settings) -> bool {
+        if time.duration_since(self.updated) > Duration::from_secs_f32(settings.sequence_timeout) {
+            return false;
+        }
+
+        let last = Shortcut::from_keys(&self.last_stroke().keys, &settings.modifier_order);
+        settings
+            .sequence_prefixes
+            .iter()
+            .any(|prefix| prefix.matches(&last, true))
+    }
+
+    /// Whether `stroke` is another press of this single-combo entry, arriving
+    /// while the entry is still on screen under the configured lifetime.
+    pub fn is_repeat_of(
+        &self,
+        stroke: &Stroke,
+        time: Instant,
+        is_current: bool,
+        settings: &Settings,
+    ) -> bool {
+        let shown_for = Duration::from_secs_f32(settings.display_time + settings.fade_time);
+        let is_on_screen = match settings.lifetime {
+            Lifetime::AlwaysLast if is_current => true,
+            Lifetime::WhileHeld => self
+                .released()
+                .is_none_or(|released| time.duration_since(released) <= shown_for),
+            _ => time.duration_since(self.updated) <= shown_for,
+        };
+        self.strokes.len() == 1 && self.last_stroke().keys == stroke.keys && is_on_screen
+    }
+
+    /// Counts another press, showing the new press's timing.
+    pub fn add_press(&mut self, stroke: Stroke, time: Instant) {
+        self.presses += 1;
+        self.pressed = time;
+        self.replace_last_stroke(stroke, time);
+    }
+
+    pub fn add_repeat(&mut self, time: Instant) {
+        self.repeats += 1;
+        self.updated = time;
+    }
+
+    pub fn push_stroke(&mut self, stroke: Stroke, time: Instant) {
+        self.strokes.push(stroke);
+        self.updated = time;
+    }
+
+    /// Replaces the last stroke after a provisional modifier combo grew.
+    pub fn replace_last_stroke(&mut self, stroke: Stroke, time: Instant) {
+        if let Some(last) = self.strokes.last_mut() {
+            *last = stroke;
+        }
+        self.updated = time;
+    }
+
+    /// Records a key release in every stroke still holding the key.
+    pub fn release(&mut self, vk: u32, time: Instant) {
+        for stroke in &mut self.strokes {
+            if let Some(hold) = stroke.holds.get_mut(&vk) {
+                hold.released.get_or_insert(time);
+            }
+        }
+    }
+
+    /// When the last key of the entry was released, once all of them are.
+    pub fn released(&self) -> Option<Instant> {
+        self.strokes
+            .iter()
+            .flat_map(|stroke| stroke.holds.values())
+            .map(|hold| hold.released)
+            .try_fold(self.started, |latest, released| {
+                released.map(|released| latest.max(released))
+            })
+    }
+
+    pub fn segments(&mut self, settings: &Settings) -> Vec<LabelSegment> {
+        let mut segments = Vec::new();
+
+        for (i, stroke) in self.strokes.iter_mut().enumerate() {
+            if i > 0 {
+                segments.push(LabelSegment::text(", "));
+            }
+            for (j, (vk, label)) in
+                key_combination_labels(&mut stroke.keys, stroke.layout, settings)
+                    .into_iter()
+                    .enumerate()
+            {
+                if j > 0 {
+                    segments.push(LabelSegment::text(KEY_SEPARATOR));
+                }
+                segments.push(LabelSegment {
+                    text: label,
+                    hold: stroke.holds.get(&vk).copied(),
+                });
+            }
+        }
+
+        if self.presses > 1 {
+            segments.push(LabelSegment::text(format!(" ×{}", self.presses)));
+        }
+        if self.repeats > 0 {
+            segments.push(LabelSegment::text(format!(" 🔁{}", self.repeats)));
+        }
+        segments
+    }
+
+    pub fn label(&mut self, settings: &Settings) -> String {
+        self.segments(settings)
+            .into_iter()
+            .map(|segment| segment.text)
+            .collect()
+    }
+}
+
+/// Adds a stroke from the combo tracker to the current entry, or starts a new
+/// entry and moves the current one to the front of `history`.
+///
+/// A modifier press starts a provisional entry ("Ctrl") that the rest of the
+/// combo then extends. When the finished combo repeats the entry the
+/// provisional one displaced, that entry is restored and counts the press.
+pub fn add_stroke(
+    current: &mut Option<ComboEntry>,
+    history: &mut VecDeque<ComboEntry>,
+    change: ComboChange,
+    stroke: Stroke,
+    time: Instant,
+    settings: &Settings,
+) {
+    if change == ComboChange::Started {
+        match current {
+            Some(entry) if entry.continues_sequence(time, settings) => {
+                entry.push_stroke(stroke, time);
+            }
+            Some(entry) if entry.is_repeat_of(&stroke, time, true, settings) => {
+                entry.add_press(stroke, time);
+            }
+            _ => {
+                let mut entry = ComboEntry::new(stroke, time);
+                entry.displaced_current = current.is_some();
+                if let Some(previous) = current.replace(entry) {
+                    history.push_front(previous);
+                    history.truncate(settings.history_length.max(1));
+                }
+            }
+        }
+    } else {
+        match (current.as_mut(), history.front_mut()) {
+            (Some(entry), Some(previous))
+                if entry.strokes.len() == 1
+                    && previous.is_repeat_of(&stroke, time, entry.displaced_current, settings) =>
+            {
+                previous.add_press(stroke, time);
+                *current = history.pop_front();
+            }
+            (Some(entry), _) => entry.replace_last_stroke(stroke, time),
+            (None, _) => *current = Some(ComboEntry::new(stroke, time)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CTRL: u32 = 0xA2;
+    const SHIFT: u32 = 0xA0;
+
+    fn stroke(keys: &[u32], time: Instant) -> Stroke {
+        let hold = KeyHold {
+            pressed: time,
+            released: None,
+        };
+        Stroke {
+            keys: keys.iter().copied().collect(),
+            holds: keys.iter().map(|&vk| (vk, hold)).collect(),
+            layout: HKL::default(),
+        }
+    }
+
+    // Presses Ctrl, then completes the combo with `key`, as the tracker
+    // reports it
+    fn press(
+        current: &mut Option<ComboEntry>,
+        history: &mut VecDeque<ComboEntry>,
+        keys: &[u32],
+        time: Instant,
+        settings: &Settings,
+    ) {
+        add_stroke(
+            current,
+            history,
+            ComboChange::Started,
+            stroke(&keys[..1], time),
+            time,
+            settings,
+        );
+        add_stroke(
+            current,
+            history,
+            ComboChange::Extended,
+            stroke(keys, time),
+            time,
+            settings,
+        );
+    }
+
+    fn lifetime_settings(lifetime: Lifetime) -> Settings {
+        let mut settings = Settings::default();
+        settings.lifetime = lifetime;
+        settings
+    }
+
+    #[test]
+    fn counts_repeat_of_the_last_entry_under_always_last() {
+        let settings = lifetime_settings(Lifetime::AlwaysLast);
+        let (mut current, mut history) = (None, VecDeque::new());
+        let start = Instant::now();
+        let ctrl_z = [CTRL, u32::from(b'Z')];
+
+        press(&mut current, &mut history, &ctrl_z, start, &settings);
+        // Long after the display and fade time, the entry is still shown
+        let later = start + Duration::from_secs(10);
+        press(&mut current, &mut history, &ctrl_z, later, &settings);
+
+        let entry = current.as_ref().unwrap();
+        assert_eq!(entry.presses, 2);
+        assert_eq!(entry.pressed, later);
+        assert!(history.is_empty());
+    }
+
+    #[test]
+    fn history_repeat_needs_the_entry_on_screen() {
+        let settings = lifetime_settings(Lifetime::AlwaysLast);
+        let (mut current, mut history) = (None, VecDeque::new());
+        let start = Instant::now();
+        let ctrl_z = [CTRL, u32::from(b'Z')];
+
+        press(&mut current, &mut history, &ctrl_z, start, &settings);
+        press(&mut current, &mut history, &ctrl_z, start, &settings);
+        // Ctrl+Shift+Z grows out of a restored entry, not a provisional one
+        let later = start + Duration::from_secs(10);
+        add_stroke(
+            &mut current,
+            &mut history,
+            ComboChange::Extended,
+            stroke(&[CTRL, SHIFT, u32::from(b'Z')], later),
+            later,
+            &settings,
+        );
+        press(&mut current, &mut history, &ctrl_z, later, &settings);
+
+        assert_eq!(current.as_ref().unwrap().presses, 1);
+        assert_eq!(history.len(), 1);
+    }
+
+    #[test]
+    fn timed_repeat_starts_a_new_entry_after_fading() {
+        let settings = lifetime_settings(Lifetime::Timed);
+        let (mut current, mut history) = (None, VecDeque::new());
+        let start = Instant::now();
+        let ctrl_z = [CTRL, u32::from(b'Z')];
+
+        press(&mut current, &mut history, &ctrl_z, start, &settings);
+        press(
+            &mut current,
+            &mut history,
+            &ctrl_z,
+            start + Duration::from_secs(1),
+            &settings,
+        );
+        assert_eq!(current.as_ref().unwrap().presses, 2);
+
+        press(
+            &mut current,
+            &mut history,
+            &ctrl_z,
+            start + Duration::from_secs(10),
+            &settings,
+        );
+        assert_eq!(current.as_ref().unwrap().presses, 1);
+        assert_eq!(history.len(), 1);
+    }
+}
@@ -0,0 +1,142 @@
+use std::time::{Duration, Instant};
+
+use crate::{
+    icon::{DeviceIcon, IconError},
+    platform,
+};
+
+// Built-in artwork, replaced by `gamepad.svg` in the icons directory
+const GAMEPAD_SVG: &str = include_str!("icons/gamepad.svg");
+// SVG regions in the order of the button state, which follows the XInput
+// button bits; the triggers take the two bits XInput leaves unused
+const GAMEPAD_REGIONS: [&str; 16] = [
+    "up", "down", "left", "right", "menu", "view", "ls", "rs", "lb", "rb", "lt", "rt", "a", "b",
+    "x", "y",
+];
+const LEFT_TRIGGER: usize = 10;
+const RIGHT_TRIGGER: usize = 11;
+// Trigger travel out of 255 that counts as pressed, XInput's own threshold
+const TRIGGER_THRESHOLD: u8 = 30;
+
+// XInput supports this many controllers
+const MAX_GAMEPADS: u32 = 4;
+// Asking XInput about an empty slot is slow, so they are only searched for a
+// controller this often
+const SCAN_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Which buttons are held, in `GAMEPAD_REGIONS` order.
+pub type GamepadButtons = [bool; 16];
+
+/// Button state from the XInput button bits and the trigger positions.
+pub fn gamepad_buttons(bits: u16, left_trigger: u8, right_trigger: u8) -> GamepadButtons {
+    let mut buttons: GamepadButtons = std::array::from_fn(|bit| bits & (1 << bit) != 0);
+    buttons[LEFT_TRIGGER] = left_trigger > TRIGGER_THRESHOLD;
+    buttons[RIGHT_TRIGGER] = right_trigger > TRIGGER_THRESHOLD;
+    buttons
+}
+
+/// The first connected controller, polled once per frame.
+#[derive(Default)]
+pub struct Gamepad {
+    user_index: Option<u32>,
+    last_scan: Option<Instant>,
+    buttons: Option<GamepadButtons>,
+}
+
+impl Gamepad {
+    /// Reads the controller, or looks for one when none is connected.
+    pub fn poll(&mut self, now: Instant) {
+        if let Some(user_index) = self.user_index {
+            self.buttons = platform::gamepad_state(user_index);
+            if self.buttons.is_some() {
+                return;
+            }
+            self.user_index = None;
+        }
+        if self
+            .last_scan
+            .is_some_and(|last| now.duration_since(last) < SCAN_INTERVAL)
+        {
+            return;
+        }
+        self.last_scan = Some(now);
+        for user_index in 0..MAX_GAMEPADS {
+            if let Some(buttons) = platform::gamepad_state(user_index) {
+                self.user_index = Some(user_index);
+                self.buttons = Some(buttons);
+                return;
+            }
+        }
+    }
+
+    /// Held buttons, or None while no controller is connected.
+    pub fn buttons(&self) -> Option<&GamepadButtons> {
+        self.buttons.as_ref()
+    }
+}
+
+/// Gamepad artwork from `gamepad.svg` in `icon::icons_dir`, or the built-in one.
+pub fn load_gamepad_icon() -> Result<DeviceIcon, IconError> {
+    DeviceIcon::load("gamepad.svg", GAMEPAD_SVG, &GAMEPAD_REGIONS)
+}
+
+pub fn builtin_gamepad_icon() -> DeviceIcon {
+    DeviceIcon::parse(GAMEPAD_SVG, &GAMEPAD_REGIONS).expect("built-in gamepad icon is valid")
+}
+
+pub fn draw_gamepad(ui: &mut egui::Ui, icon: &mut DeviceIcon, buttons: &GamepadButtons) {
+    let (_id, rect) = ui.allocate_space(egui::vec2(86.0, 64.0));
+    let painter = ui.painter_at(rect);
+
+    let visuals = ui.visuals();
+    let is_held = |region: &str| {
+        GAMEPAD_REGIONS
+            .iter()
+            .position(|&name| name == region)
+            .is_some_and(|button| buttons[button])
+    };
+    icon.paint(
+        &painter,
+        rect.shrink(2.0),
+        is_held,
+        visuals.text_color(),
+        visuals.weak_text_color(),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_xinput_bits_to_regions() {
+        // XINPUT_GAMEPAD_DPAD_UP, _START, _LEFT_SHOULDER, _A and _Y
+        let buttons = gamepad_buttons(0x0001 | 0x0010 | 0x0100 | 0x1000 | 0x8000, 0, 0);
+        let held: Vec<&str> = GAMEPAD_REGIONS
+            .iter()
+            .zip(buttons)
+            .filter_map(|(&name, is_held)| is_held.then_some(name))
+            .collect();
+        assert_eq!(held, ["up", "menu", "lb", "a", "y"]);
+    }
+
+    #[test]
+    fn triggers_press_past_the_threshold() {
+        let buttons = gamepad_buttons(0, TRIGGER_THRESHOLD, TRIGGER_THRESHOLD + 1);
+        assert!(!buttons[LEFT_TRIGGER]);
+        assert!(buttons[RIGHT_TRIGGER]);
+        // Bits XInput leaves unused don't press the triggers
+        assert_eq!(gamepad_buttons(0x0C00, 0, 0), [false; 16]);
+    }
+
+    #[test]
+    fn builtin_icon_has_every_region() {
+        builtin_gamepad_icon();
+        for region in GAMEPAD_REGIONS {
+            assert!(
+                GAMEPAD_SVG.contains(&format!("id=\"{region}\"")),
+                "{region}"
+            );
+        }
+    }
+}
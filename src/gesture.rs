@@ -0,0 +1,345 @@
+// Mouse gesture recognition from raw button and movement events.
+//
+// - Presses of the same button close together in time and space count up:
+//   the second down is a double click, the third a triple click. Like
+//   Windows, the interval is measured from one press to the next.
+// - A held button that travels past the drag threshold starts a drag; the
+//   release ends it and reports the straight-line distance.
+// - A button held in place past the long-press time is a long-press.
+// Drags and long-presses are not clicks, so they reset the click count.
+
+use std::time::{Duration, Instant};
+
+use crate::{
+    key_names::{VK_MOUSE_BUTTON, VK_MOUSE_DRAG},
+    locale::{self, tr, Locale},
+    mouse_input::DragDetector,
+    settings::Settings,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MouseAction {
+    Down(usize),
+    Up(usize),
+    Move,
+    /// Signed wheel delta; positive is up, or right for horizontal wheels.
+    Wheel {
+        delta: i32,
+        is_horizontal: bool,
+    },
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct MouseEvent {
+    pub action: MouseAction,
+    pub x: i32,
+    pub y: i32,
+    pub time: Instant,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Gesture {
+    /// The `count`th press in a row, two or more.
+    MultiClick {
+        button: usize,
+        count: u32,
+    },
+    DragStart {
+        button: usize,
+    },
+    /// `distance` is in pixels between press and release.
+    DragEnd {
+        button: usize,
+        distance: f32,
+    },
+    LongPress {
+        button: usize,
+    },
+}
+
+impl Gesture {
+    pub fn label(&self, locale: Locale) -> String {
+        match *self {
+            Gesture::MultiClick { button, count } => {
+                let name = button_name(locale, VK_MOUSE_BUTTON[button]);
+                match count {
+                    2 => tr(locale, "Double {button}").replace("{button}", name),
+                    3 => tr(locale, "Triple {button}").replace("{button}", name),
+                    _ => format!("{name} ×{count}"),
+                }
+            }
+            Gesture::DragStart { button } => tr(locale, "{drag} start")
+                .replace("{drag}", button_name(locale, VK_MOUSE_DRAG[button])),
+            Gesture::DragEnd { button, distance } => tr(locale, "{drag} end, {distance} px")
+                .replace("{drag}", button_name(locale, VK_MOUSE_DRAG[button]))
+                .replace("{distance}", &format!("{distance:.0}")),
+            Gesture::LongPress { button } => tr(locale, "Long {button}")
+                .replace("{button}", button_name(locale, VK_MOUSE_BUTTON[button])),
+        }
+    }
+}
+
+fn button_name(locale: Locale, vk: u32) -> &'static str {
+    // Every mouse pseudo key has a name
+    locale::key_name(locale, crate::key_names::vk_name(vk).unwrap_or_default())
+}
+
+struct Press {
+    time: Instant,
+    is_long: bool,
+}
+
+struct Click {
+    pos: (i32, i32),
+    time: Instant,
+    count: u32,
+}
+
+pub struct GestureRecognizer {
+    drags: DragDetector,
+    // Largest offset between presses that still counts as a double click
+    double_click_size: (i32, i32),
+    presses: [Option<Press>; 5],
+    last_clicks: [Option<Click>; 5],
+}
+
+impl GestureRecognizer {
+    /// Sizes are in pixels, normally from the system metrics
+    /// SM_CXDRAG/SM_CYDRAG and SM_CXDOUBLECLK/SM_CYDOUBLECLK.
+    pub fn new(drag_threshold: (i32, i32), double_click_size: (i32, i32)) -> Self {
+        Self {
+            drags: DragDetector::new(drag_threshold),
+            double_click_size,
+            presses: Default::default(),
+            last_clicks: Default::default(),
+        }
+    }
+
+    pub fn handle(&mut self, event: &MouseEvent, settings: &Settings) -> Vec<Gesture> {
+        let pos = (event.x, event.y);
+        match event.action {
+            MouseAction::Down(button) => {
+                self.presses[button] = Some(Press {
+                    time: event.time,
+                    is_long: false,
+                });
+                self.drags.press(button, pos);
+
+                let (width, height) = self.double_click_size;
+                let count = match &self.last_clicks[button] {
+                    Some(click)
+                        if event.time.duration_since(click.time) <= settings.multi_click_time()
+                            && (pos.0 - click.pos.0).abs() <= width / 2
+                            && (pos.1 - click.pos.1).abs() <= height / 2 =>
+                    {
+                        click.count + 1
+                    }
+                    _ => 1,
+                };
+                self.last_clicks[button] = Some(Click {
+                    pos,
+                    time: event.time,
+                    count,
+                });
+
+                if count > 1 {
+                    vec![Gesture::MultiClick { button, count }]
+                } else {
+                    Vec::new()
+                }
+            }
+            MouseAction::Up(button) => {
+                self.presses[button] = None;
+                match self.drags.release(button) {
+                    Some((start, true)) => {
+                        let distance = ((pos.0 - start.0) as f32).hypot((pos.1 - start.1) as f32);
+                        vec![Gesture::DragEnd { button, distance }]
+                    }
+                    _ => Vec::new(),
+                }
+            }
+            MouseAction::Move => self
+                .drags
+                .moved(pos)
+                .into_iter()
+                .map(|button| {
+                    self.last_clicks[button] = None;
+                    Gesture::DragStart { button }
+                })
+                .collect(),
+            MouseAction::Wheel { .. } => Vec::new(),
+        }
+    }
+
+    /// Reports buttons that have now been held in place past the long-press
+    /// time. Called every frame, since holding still produces no events.
+    pub fn poll(&mut self, now: Instant, settings: &Settings) -> Vec<Gesture> {
+        let long_press = Duration::from_secs_f32(settings.long_press_time);
+        let mut gestures = Vec::new();
+        for button in 0..5 {
+            let Some(press) = &mut self.presses[button] else {
+                continue;
+            };
+            if !self.drags.is_dragging(button)
+                && !press.is_long
+                && now.duration_since(press.time) >= long_press
+            {
+                press.is_long = true;
+                self.last_clicks[button] = None;
+                gestures.push(Gesture::LongPress { button });
+            }
+        }
+        gestures
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEFT: usize = 0;
+
+    fn settings() -> Settings {
+        let mut settings = Settings::default();
+        settings.is_system_double_click = false;
+        settings.double_click_time = 0.5;
+        settings.long_press_time = 0.6;
+        settings
+    }
+
+    fn recognizer() -> GestureRecognizer {
+        GestureRecognizer::new((4, 4), (4, 4))
+    }
+
+    fn event(action: MouseAction, pos: (i32, i32), time: Instant) -> MouseEvent {
+        MouseEvent {
+            action,
+            x: pos.0,
+            y: pos.1,
+            time,
+        }
+    }
+
+    // Press and release in place at `time`, returning the press's gestures
+    fn click(
+        recognizer: &mut GestureRecognizer,
+        pos: (i32, i32),
+        time: Instant,
+        settings: &Settings,
+    ) -> Vec<Gesture> {
+        let gestures = recognizer.handle(&event(MouseAction::Down(LEFT), pos, time), settings);
+        recognizer.handle(&event(MouseAction::Up(LEFT), pos, time), settings);
+        gestures
+    }
+
+    #[test]
+    fn counts_clicks_within_the_interval() {
+        let settings = settings();
+        let mut recognizer = recognizer();
+        let start = Instant::now();
+        let step = Duration::from_millis(400);
+
+        assert!(click(&mut recognizer, (10, 10), start, &settings).is_empty());
+        assert_eq!(
+            click(&mut recognizer, (11, 9), start + step, &settings),
+            [Gesture::MultiClick {
+                button: LEFT,
+                count: 2
+            }]
+        );
+        // Measured from the previous press, not the first
+        assert_eq!(
+            click(&mut recognizer, (10, 10), start + step * 2, &settings),
+            [Gesture::MultiClick {
+                button: LEFT,
+                count: 3
+            }]
+        );
+    }
+
+    #[test]
+    fn slow_or_distant_clicks_start_over() {
+        let settings = settings();
+        let mut recognizer = recognizer();
+        let start = Instant::now();
+
+        click(&mut recognizer, (10, 10), start, &settings);
+        let late = start + Duration::from_millis(600);
+        assert!(click(&mut recognizer, (10, 10), late, &settings).is_empty());
+
+        // Farther than half the double-click rectangle
+        let soon = late + Duration::from_millis(100);
+        assert!(click(&mut recognizer, (13, 10), soon, &settings).is_empty());
+
+        // Other buttons keep their own count
+        let right = recognizer.handle(&event(MouseAction::Down(1), (13, 10), soon), &settings);
+        assert!(right.is_empty());
+    }
+
+    #[test]
+    fn reports_drag_start_and_distance() {
+        let settings = settings();
+        let mut recognizer = recognizer();
+        let now = Instant::now();
+
+        recognizer.handle(&event(MouseAction::Down(LEFT), (0, 0), now), &settings);
+        // Within the threshold on both axes
+        let moved = recognizer.handle(&event(MouseAction::Move, (4, -4), now), &settings);
+        assert!(moved.is_empty());
+        let moved = recognizer.handle(&event(MouseAction::Move, (5, 0), now), &settings);
+        assert_eq!(moved, [Gesture::DragStart { button: LEFT }]);
+        // Only once per press
+        let moved = recognizer.handle(&event(MouseAction::Move, (20, 0), now), &settings);
+        assert!(moved.is_empty());
+
+        let released = recognizer.handle(&event(MouseAction::Up(LEFT), (30, 40), now), &settings);
+        assert_eq!(
+            released,
+            [Gesture::DragEnd {
+                button: LEFT,
+                distance: 50.0
+            }]
+        );
+
+        // A drag isn't a click, so the next press starts counting again
+        assert!(click(&mut recognizer, (30, 40), now, &settings).is_empty());
+    }
+
+    #[test]
+    fn reports_long_press_once() {
+        let settings = settings();
+        let mut recognizer = recognizer();
+        let start = Instant::now();
+
+        recognizer.handle(&event(MouseAction::Down(LEFT), (0, 0), start), &settings);
+        assert!(recognizer
+            .poll(start + Duration::from_millis(500), &settings)
+            .is_empty());
+        let held = start + Duration::from_millis(700);
+        assert_eq!(
+            recognizer.poll(held, &settings),
+            [Gesture::LongPress { button: LEFT }]
+        );
+        assert!(recognizer
+            .poll(held + Duration::from_secs(1), &settings)
+            .is_empty());
+        recognizer.handle(&event(MouseAction::Up(LEFT), (0, 0), held), &settings);
+
+        // Nor is a long-press, even when the next press is quick
+        let next = held + Duration::from_millis(100);
+        assert!(click(&mut recognizer, (0, 0), next, &settings).is_empty());
+    }
+
+    #[test]
+    fn dragging_is_not_a_long_press() {
+        let settings = settings();
+        let mut recognizer = recognizer();
+        let start = Instant::now();
+
+        recognizer.handle(&event(MouseAction::Down(LEFT), (0, 0), start), &settings);
+        recognizer.handle(&event(MouseAction::Move, (0, 10), start), &settings);
+        assert!(recognizer
+            .poll(start + Duration::from_secs(2), &settings)
+            .is_empty());
+    }
+}
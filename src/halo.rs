@@ -0,0 +1,107 @@
+// Cursor halo: a translucent disc that follows the pointer, with an optional
+// trail of its recent positions that thins and fades out.
+//
+// Positions come from the mouse hook in physical screen pixels and map to the
+// pointer overlay the same way as click ripples (see ripple.rs).
+
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use eframe::egui;
+
+// How long a trail point stays visible
+const TRAIL_TIME: Duration = Duration::from_millis(300);
+// Trail half-width relative to the halo radius where the trail meets it
+const TRAIL_WIDTH: f32 = 0.6;
+
+#[derive(Default)]
+pub struct Halo {
+    pos: Option<(i32, i32)>,
+    // Recent positions, oldest first
+    trail: VecDeque<((i32, i32), Instant)>,
+}
+
+impl Halo {
+    /// Records the pointer at physical screen position (`x`, `y`).
+    pub fn move_to(&mut self, x: i32, y: i32, time: Instant) {
+        self.pos = Some((x, y));
+        // High-rate mice report a thousand moves a second, so expire them here
+        // rather than only when painting
+        while self
+            .trail
+            .front()
+            .is_some_and(|&(_, t)| time.saturating_duration_since(t) >= TRAIL_TIME)
+        {
+            self.trail.pop_front();
+        }
+        self.trail.push_back(((x, y), time));
+    }
+
+    /// Paints the halo, and the trail when `is_trail` is set. `origin` is the
+    /// physical screen position of the painter's top-left corner and `radius`
+    /// is in points.
+    pub fn paint(
+        &mut self,
+        painter: &egui::Painter,
+        origin: (i32, i32),
+        color: egui::Color32,
+        radius: f32,
+        is_trail: bool,
+        now: Instant,
+    ) {
+        let Some(pos) = self.pos else {
+            return;
+        };
+        let pixels_per_point = painter.pixels_per_point();
+        let to_point = |(x, y): (i32, i32)| {
+            egui::pos2(
+                (x - origin.0) as f32 / pixels_per_point,
+                (y - origin.1) as f32 / pixels_per_point,
+            )
+        };
+
+        self.trail
+            .retain(|&(_, t)| now.saturating_duration_since(t) < TRAIL_TIME);
+        let center = to_point(pos);
+        if is_trail {
+            // One triangle strip narrowing towards the oldest point, so the
+            // translucent color doesn't stack up where segments would overlap.
+            // It stops at the halo for the same reason.
+            let mut points: Vec<(egui::Pos2, f32)> = self
+                .trail
+                .iter()
+                .map(|&(pos, t)| {
+                    let age =
+                        now.saturating_duration_since(t).as_secs_f32() / TRAIL_TIME.as_secs_f32();
+                    (to_point(pos), 1.0 - age)
+                })
+                .filter(|&(point, _)| point.distance(center) > radius)
+                .collect();
+            // End on the chord whose half-length is the trail's half-width, so
+            // the strip's corners touch the halo's edge
+            let chord_distance = (1.0 - TRAIL_WIDTH * TRAIL_WIDTH).sqrt() * radius;
+            if let Some(&(last, _)) = points.last() {
+                points.push((center + (last - center).normalized() * chord_distance, 1.0));
+            }
+            let mut mesh = egui::Mesh::default();
+            for (i, &(point, life)) in points.iter().enumerate() {
+                let prev = points[i.saturating_sub(1)].0;
+                let next = points[(i + 1).min(points.len() - 1)].0;
+                let normal = (next - prev).normalized().rot90() * radius * TRAIL_WIDTH * life;
+                let vertex_color = color.gamma_multiply(life);
+                mesh.colored_vertex(point + normal, vertex_color);
+                mesh.colored_vertex(point - normal, vertex_color);
+                if i > 0 {
+                    let n = 2 * i as u32;
+                    mesh.add_triangle(n - 2, n - 1, n);
+                    mesh.add_triangle(n - 1, n + 1, n);
+                }
+            }
+            painter.add(mesh);
+        }
+
+        painter.circle_filled(center, radius, color);
+    }
+}
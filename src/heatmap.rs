@@ -0,0 +1,459 @@
+// Key usage heatmap: per-key press counts colored over the keyboard layout.
+//
+// Presses are counted by scan code, like the keyboard view lights keys, so
+// the map shows physical wear whatever the active keyboard layout. Colors
+// follow the logarithm of the count, since Space and a few letters would
+// otherwise wash out every other key.
+//
+// Sessions are saved as TOML in `heatmaps/` next to the executable, one file
+// per run named after its start time, with hexadecimal scan codes as keys:
+//
+//     [counts]
+//     1E = 5120
+//     E048 = 312
+//
+// Exported PNGs go to the same directory. They show the key shapes and colors
+// without legends.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+
+use crate::keyboard::{self, KeyboardLayout, LayoutKey};
+
+// Exported image size in pixels per key unit
+const PNG_KEY_SIZE: f32 = 48.0;
+const PNG_MARGIN: u32 = 16;
+const PNG_SCALE_HEIGHT: u32 = 12;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorScale {
+    Heat,
+    Viridis,
+    CoolWarm,
+    Grayscale,
+}
+
+impl ColorScale {
+    pub const ALL: [ColorScale; 4] = [
+        ColorScale::Heat,
+        ColorScale::Viridis,
+        ColorScale::CoolWarm,
+        ColorScale::Grayscale,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ColorScale::Heat => "Heat",
+            ColorScale::Viridis => "Viridis",
+            ColorScale::CoolWarm => "Cool to warm",
+            ColorScale::Grayscale => "Grayscale",
+        }
+    }
+
+    fn stops(self) -> &'static [[u8; 3]] {
+        match self {
+            ColorScale::Heat => &[[40, 40, 40], [180, 0, 0], [255, 140, 0], [255, 255, 160]],
+            ColorScale::Viridis => &[
+                [68, 1, 84],
+                [59, 82, 139],
+                [33, 145, 140],
+                [94, 201, 98],
+                [253, 231, 37],
+            ],
+            ColorScale::CoolWarm => &[[59, 76, 192], [221, 221, 221], [180, 4, 38]],
+            ColorScale::Grayscale => &[[32, 32, 32], [255, 255, 255]],
+        }
+    }
+
+    /// Color at `t` between 0 (least used) and 1 (most used).
+    pub fn color(self, t: f32) -> egui::Color32 {
+        let stops = self.stops();
+        let position = t.clamp(0.0, 1.0) * (stops.len() - 1) as f32;
+        let i = (position as usize).min(stops.len() - 2);
+        let f = position - i as f32;
+        let [r, g, b] = std::array::from_fn(|c| {
+            (stops[i][c] as f32 + (stops[i + 1][c] as f32 - stops[i][c] as f32) * f).round() as u8
+        });
+        egui::Color32::from_rgb(r, g, b)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HeatmapSource {
+    Session,
+    /// Every saved session plus the current one.
+    AllSessions,
+}
+
+impl HeatmapSource {
+    pub fn label(self) -> &'static str {
+        match self {
+            HeatmapSource::Session => "This session",
+            HeatmapSource::AllSessions => "All sessions",
+        }
+    }
+}
+
+/// Heatmap actions requested from the settings window.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HeatmapRequest {
+    SaveSession,
+    ExportPng,
+    ResetSession,
+}
+
+#[derive(Debug)]
+pub enum HeatmapError {
+    Io(PathBuf, io::Error),
+    Parse(PathBuf, toml::de::Error),
+    Serialize(toml::ser::Error),
+    Image(PathBuf, image::ImageError),
+    NoDirectory,
+}
+
+impl fmt::Display for HeatmapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HeatmapError::Io(path, err) => write!(f, "cannot access {}: {err}", path.display()),
+            HeatmapError::Parse(path, err) => write!(f, "invalid {}: {err}", path.display()),
+            HeatmapError::Serialize(err) => write!(f, "cannot encode counts: {err}"),
+            HeatmapError::Image(path, err) => write!(f, "cannot write {}: {err}", path.display()),
+            HeatmapError::NoDirectory => write!(f, "cannot locate the heatmap directory"),
+        }
+    }
+}
+
+impl std::error::Error for HeatmapError {}
+
+#[derive(Clone, Debug, Default)]
+pub struct KeyCounts(HashMap<u32, u64>);
+
+#[derive(Serialize, Deserialize)]
+struct CountsFile {
+    counts: BTreeMap<String, u64>,
+}
+
+impl KeyCounts {
+    pub fn add(&mut self, scan_code: u32) {
+        *self.0.entry(scan_code).or_default() += 1;
+    }
+
+    pub fn get(&self, scan_code: u32) -> u64 {
+        self.0.get(&scan_code).copied().unwrap_or(0)
+    }
+
+    pub fn max(&self) -> u64 {
+        self.0.values().copied().max().unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    pub fn merge(&mut self, other: &KeyCounts) {
+        for (&scan_code, &count) in &other.0 {
+            *self.0.entry(scan_code).or_default() += count;
+        }
+    }
+
+    /// Position of `scan_code` on the color scale, 0 for unused keys.
+    fn level(&self, scan_code: u32, max: u64) -> f32 {
+        match self.get(scan_code) {
+            0 => 0.0,
+            count => ((count as f32).ln_1p() / (max as f32).ln_1p()).min(1.0),
+        }
+    }
+
+    fn load(path: &Path) -> Result<Self, HeatmapError> {
+        let text = fs::read_to_string(path).map_err(|err| HeatmapError::Io(path.into(), err))?;
+        let file: CountsFile =
+            toml::from_str(&text).map_err(|err| HeatmapError::Parse(path.into(), err))?;
+        // Entries that aren't scan codes are skipped rather than failing the file
+        Ok(Self(
+            file.counts
+                .iter()
+                .filter_map(|(code, &count)| Some((u32::from_str_radix(code, 16).ok()?, count)))
+                .collect(),
+        ))
+    }
+
+    fn save(&self, path: &Path) -> Result<(), HeatmapError> {
+        let file = CountsFile {
+            counts: self
+                .0
+                .iter()
+                .map(|(&code, &count)| (format!("{code:X}"), count))
+                .collect(),
+        };
+        let text = toml::to_string(&file).map_err(HeatmapError::Serialize)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|err| HeatmapError::Io(dir.into(), err))?;
+        }
+        fs::write(path, text).map_err(|err| HeatmapError::Io(path.into(), err))
+    }
+}
+
+/// Directory holding saved sessions and exported images, next to the
+/// executable.
+pub fn heatmaps_dir() -> Option<PathBuf> {
+    Some(std::env::current_exe().ok()?.parent()?.join("heatmaps"))
+}
+
+fn unix_time(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+/// Press counts of this run, saved to its own file in `heatmaps_dir`.
+pub struct Session {
+    pub counts: KeyCounts,
+    started: SystemTime,
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self {
+            counts: KeyCounts::default(),
+            started: SystemTime::now(),
+        }
+    }
+}
+
+impl Session {
+    fn path(&self) -> Option<PathBuf> {
+        Some(heatmaps_dir()?.join(format!("{}.toml", unix_time(self.started))))
+    }
+
+    /// Writes the counts so far, replacing this session's earlier save.
+    pub fn save(&self) -> Result<PathBuf, HeatmapError> {
+        let path = self.path().ok_or(HeatmapError::NoDirectory)?;
+        self.counts.save(&path)?;
+        Ok(path)
+    }
+
+    /// Sum of every saved session other than this one, with the errors of
+    /// the session files that couldn't be read and were left out.
+    pub fn load_saved(&self) -> Result<(KeyCounts, Vec<HeatmapError>), HeatmapError> {
+        match heatmaps_dir() {
+            Some(dir) => load_sessions(&dir, self.path().as_deref()),
+            None => Ok(Default::default()),
+        }
+    }
+}
+
+// Sums the session files in `dir` other than `own`. A file that fails to load
+// is skipped, so one corrupt session doesn't hide all the others.
+fn load_sessions(
+    dir: &Path,
+    own: Option<&Path>,
+) -> Result<(KeyCounts, Vec<HeatmapError>), HeatmapError> {
+    let mut total = KeyCounts::default();
+    let mut errors = Vec::new();
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok((total, errors)),
+        Err(err) => return Err(HeatmapError::Io(dir.into(), err)),
+    };
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        if path.extension().is_some_and(|ext| ext == "toml") && Some(path.as_path()) != own {
+            match KeyCounts::load(&path) {
+                Ok(counts) => total.merge(&counts),
+                Err(err) => errors.push(err),
+            }
+        }
+    }
+    Ok((total, errors))
+}
+
+/// Text color that stays readable on `fill`.
+fn legend_color(fill: egui::Color32) -> egui::Color32 {
+    let luma = 0.299 * fill.r() as f32 + 0.587 * fill.g() as f32 + 0.114 * fill.b() as f32;
+    if luma > 140.0 {
+        egui::Color32::BLACK
+    } else {
+        egui::Color32::WHITE
+    }
+}
+
+fn key_fill(
+    key: &LayoutKey,
+    counts: &KeyCounts,
+    max: u64,
+    scale: ColorScale,
+) -> Option<egui::Color32> {
+    let scan_code = key.scan_code?;
+    (counts.get(scan_code) > 0).then(|| scale.color(counts.level(scan_code, max)))
+}
+
+/// Draws `layout` with each key colored by its press count. Unused keys keep
+/// the normal key color.
+pub fn draw_heatmap(
+    ui: &mut egui::Ui,
+    layout: &KeyboardLayout,
+    counts: &KeyCounts,
+    scale: ColorScale,
+) {
+    let visuals = ui.visuals().clone();
+    let max = counts.max();
+    keyboard::draw_keys(ui, layout, |key| match key_fill(key, counts, max, scale) {
+        Some(fill) => (fill, legend_color(fill)),
+        None => (visuals.widgets.inactive.bg_fill, visuals.text_color()),
+    });
+}
+
+/// Renders the heatmap to a PNG in `heatmaps_dir`, with the color scale
+/// underneath, and returns its path.
+pub fn export_png(
+    layout: &KeyboardLayout,
+    counts: &KeyCounts,
+    scale: ColorScale,
+) -> Result<PathBuf, HeatmapError> {
+    let dir = heatmaps_dir().ok_or(HeatmapError::NoDirectory)?;
+    fs::create_dir_all(&dir).map_err(|err| HeatmapError::Io(dir.clone(), err))?;
+    let path = dir.join(format!("heatmap-{}.png", unix_time(SystemTime::now())));
+
+    let keys_width = (layout.size.x * PNG_KEY_SIZE).ceil() as u32;
+    let keys_height = (layout.size.y * PNG_KEY_SIZE).ceil() as u32;
+    let width = keys_width + PNG_MARGIN * 2;
+    let height = keys_height + PNG_SCALE_HEIGHT + PNG_MARGIN * 3;
+    let mut image = image::RgbaImage::from_pixel(width, height, image::Rgba([24, 24, 24, 255]));
+
+    let mut fill_rect = |rect: egui::Rect, color: egui::Color32| {
+        let x0 = rect.min.x.round().max(0.0) as u32;
+        let y0 = rect.min.y.round().max(0.0) as u32;
+        let x1 = (rect.max.x.round() as u32).min(width);
+        let y1 = (rect.max.y.round() as u32).min(height);
+        for y in y0..y1 {
+            for x in x0..x1 {
+                image.put_pixel(x, y, image::Rgba(color.to_array()));
+            }
+        }
+    };
+
+    let origin = egui::vec2(PNG_MARGIN as f32, PNG_MARGIN as f32);
+    let to_image = |rect: egui::Rect| {
+        egui::Rect::from_min_size(
+            (rect.min.to_vec2() * PNG_KEY_SIZE + origin).to_pos2(),
+            rect.size() * PNG_KEY_SIZE,
+        )
+        .shrink(PNG_KEY_SIZE * 0.05)
+    };
+    let max = counts.max();
+    for key in &layout.keys {
+        let fill = key_fill(key, counts, max, scale).unwrap_or(egui::Color32::from_gray(60));
+        for rect in std::iter::once(key.rect).chain(key.rect2) {
+            fill_rect(to_image(rect), fill);
+        }
+    }
+
+    let scale_top = (PNG_MARGIN * 2 + keys_height) as f32;
+    for x in 0..keys_width {
+        let t = x as f32 / (keys_width.max(2) - 1) as f32;
+        let column = egui::Rect::from_min_size(
+            egui::pos2((PNG_MARGIN + x) as f32, scale_top),
+            egui::vec2(1.0, PNG_SCALE_HEIGHT as f32),
+        );
+        fill_rect(column, scale.color(t));
+    }
+
+    image
+        .save(&path)
+        .map_err(|err| HeatmapError::Image(path.clone(), err))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Empty directory of its own under the system temp directory
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("heatmap-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn counts(presses: &[(u32, u64)]) -> KeyCounts {
+        KeyCounts(presses.iter().copied().collect())
+    }
+
+    #[test]
+    fn levels_follow_the_log_of_the_count() {
+        let counts = counts(&[(0x1E, 1), (0x39, 9), (0x12, 99)]);
+        let max = counts.max();
+        assert_eq!(counts.level(0x10, max), 0.0);
+        assert_eq!(counts.level(0x12, max), 1.0);
+        // ln(1 + 9) is half of ln(1 + 99)
+        assert!((counts.level(0x39, max) - 0.5).abs() < 1e-6);
+        assert!((counts.level(0x1E, max) - 2f32.ln() / 100f32.ln()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn color_scales_span_their_stops() {
+        for scale in ColorScale::ALL {
+            let [first, .., last] = scale.stops() else {
+                panic!("{} needs two stops", scale.label());
+            };
+            assert_eq!(
+                scale.color(0.0),
+                egui::Color32::from_rgb(first[0], first[1], first[2])
+            );
+            assert_eq!(
+                scale.color(1.0),
+                egui::Color32::from_rgb(last[0], last[1], last[2])
+            );
+            assert_eq!(scale.color(2.0), scale.color(1.0));
+        }
+        assert_eq!(
+            ColorScale::Grayscale.color(0.5),
+            egui::Color32::from_rgb(144, 144, 144)
+        );
+    }
+
+    #[test]
+    fn session_file_round_trips() {
+        let dir = temp_dir("round-trip");
+        let path = dir.join("1.toml");
+        let saved = counts(&[(0x1E, 5120), (0xE048, 312)]);
+        saved.save(&path).unwrap();
+
+        let text = fs::read_to_string(&path).unwrap();
+        assert!(text.contains("1E = 5120") && text.contains("E048 = 312"));
+        let loaded = KeyCounts::load(&path).unwrap();
+        assert_eq!(loaded.0, saved.0);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn skips_corrupt_sessions() {
+        let dir = temp_dir("corrupt");
+        counts(&[(0x1E, 2)]).save(&dir.join("1.toml")).unwrap();
+        counts(&[(0x1E, 3), (0x30, 1)])
+            .save(&dir.join("2.toml"))
+            .unwrap();
+        counts(&[(0x1E, 100)]).save(&dir.join("3.toml")).unwrap();
+        fs::write(dir.join("4.toml"), "[counts]\n1E = \"many\"").unwrap();
+        fs::write(dir.join("notes.txt"), "not a session").unwrap();
+
+        let (total, errors) = load_sessions(&dir, Some(&dir.join("3.toml"))).unwrap();
+        assert_eq!(total.get(0x1E), 5);
+        assert_eq!(total.get(0x30), 1);
+        assert!(matches!(&errors[..], [HeatmapError::Parse(path, _)] if path.ends_with("4.toml")));
+        fs::remove_dir_all(&dir).unwrap();
+
+        // No sessions saved yet
+        assert!(load_sessions(&dir, None).unwrap().0.is_empty());
+    }
+}
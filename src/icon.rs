@@ -0,0 +1,1116 @@
+// Device icons drawn from SVG artwork with named regions.
+//
+// Elements belong to a region through their own `id` or that of an enclosing
+// group, when the id is one of the region names the icon was loaded with (for
+// the mouse: `left`, `right`, `middle`, `x1` and `x2`). Region fills show only
+// while the region is active, such as while its button is held. All other
+// fills and every stroke are always drawn, fills first and strokes on top.
+// `currentColor` is the theme's text color in strokes and its weak text color
+// in fills, like the built-in widgets.
+//
+// The supported subset covers static artwork: `path` with every command
+// including arcs, `rect` (with rounded corners), `circle`, `ellipse`, `line`,
+// `polyline`, `polygon` and `g`; `transform`; fill, stroke, stroke-width,
+// fill-rule, stroke-linecap and stroke-linejoin as attributes or in `style`;
+// and the root `viewBox`. Gradients, text, stylesheets and clipping are not
+// supported, and `defs` are skipped.
+//
+// Layers are rasterized with tiny-skia, which handles curves, holes and
+// strokes with anti-aliasing, and kept as textures until the size on screen or
+// the theme colors change.
+
+use std::{fmt, fs, io, path::PathBuf};
+
+use eframe::egui;
+use quick_xml::events::{BytesStart, Event};
+use tiny_skia::{FillRule, LineCap, LineJoin, PathBuilder, Pixmap, Transform};
+
+// Control point distance for a quarter circle drawn as a cubic
+const KAPPA: f32 = 0.552_284_8;
+
+#[derive(Debug)]
+pub enum IconError {
+    Io(PathBuf, io::Error),
+    Xml(quick_xml::Error),
+    NoViewBox,
+    Attribute { name: String, value: String },
+}
+
+impl fmt::Display for IconError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IconError::Io(path, err) => write!(f, "cannot read {}: {err}", path.display()),
+            IconError::Xml(err) => write!(f, "invalid SVG: {err}"),
+            IconError::NoViewBox => write!(f, "SVG has neither a viewBox nor a size"),
+            IconError::Attribute { name, value } => write!(f, "invalid {name} \"{value}\""),
+        }
+    }
+}
+
+impl std::error::Error for IconError {}
+
+impl From<quick_xml::Error> for IconError {
+    fn from(err: quick_xml::Error) -> Self {
+        IconError::Xml(err)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Paint {
+    None,
+    Current,
+    Color(egui::Color32),
+}
+
+// Presentation attributes, inherited by child elements
+#[derive(Clone)]
+struct Style {
+    fill: Paint,
+    stroke: Paint,
+    stroke_width: f32,
+    fill_rule: FillRule,
+    line_cap: LineCap,
+    line_join: LineJoin,
+    transform: Transform,
+    region: Option<String>,
+    is_hidden: bool,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            fill: Paint::Color(egui::Color32::BLACK),
+            stroke: Paint::None,
+            stroke_width: 1.0,
+            fill_rule: FillRule::Winding,
+            line_cap: LineCap::Butt,
+            line_join: LineJoin::Miter,
+            transform: Transform::identity(),
+            region: None,
+            is_hidden: false,
+        }
+    }
+}
+
+struct Shape {
+    path: tiny_skia::Path,
+    style: Style,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Layer<'a> {
+    Fills,
+    Region(&'a str),
+    Strokes,
+}
+
+struct Textures {
+    // Pixel size and `currentColor` stroke and fill colors they were drawn with
+    key: ([usize; 2], egui::Color32, egui::Color32),
+    fills: Option<egui::TextureHandle>,
+    regions: Vec<(String, egui::TextureHandle)>,
+    strokes: Option<egui::TextureHandle>,
+}
+
+pub struct DeviceIcon {
+    view_box: tiny_skia::Rect,
+    shapes: Vec<Shape>,
+    regions: Vec<String>,
+    textures: Option<Textures>,
+}
+
+/// Directory holding icon overrides, next to the executable.
+pub fn icons_dir() -> Option<PathBuf> {
+    Some(std::env::current_exe().ok()?.parent()?.join("icons"))
+}
+
+impl DeviceIcon {
+    /// Loads `file_name` from `icons_dir`, or parses `builtin` when there is
+    /// no such file.
+    pub fn load(file_name: &str, builtin: &str, regions: &[&str]) -> Result<Self, IconError> {
+        match Self::load_override(file_name, regions)? {
+            Some(icon) => Ok(icon),
+            None => Self::parse(builtin, regions),
+        }
+    }
+
+    /// Loads `file_name` from `icons_dir`, or returns None when there is no
+    /// such file.
+    pub fn load_override(file_name: &str, regions: &[&str]) -> Result<Option<Self>, IconError> {
+        match icons_dir().map(|dir| dir.join(file_name)) {
+            Some(path) if path.is_file() => {
+                let text = fs::read_to_string(&path).map_err(|err| IconError::Io(path, err))?;
+                Self::parse(&text, regions).map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    pub fn parse(text: &str, regions: &[&str]) -> Result<Self, IconError> {
+        let regions: Vec<String> = regions.iter().map(|name| name.to_string()).collect();
+        let mut reader = quick_xml::Reader::from_str(text);
+        let mut stack = vec![Style::default()];
+        let mut view_box = None;
+        let mut shapes = Vec::new();
+
+        loop {
+            let (element, is_empty) = match reader.read_event()? {
+                Event::Start(element) => (element, false),
+                Event::Empty(element) => (element, true),
+                Event::End(_) => {
+                    stack.pop();
+                    continue;
+                }
+                Event::Eof => break,
+                _ => continue,
+            };
+
+            let attributes = attributes(&element)?;
+            let attribute = |name: &str| {
+                attributes
+                    .iter()
+                    .find(|(key, _)| key == name)
+                    .map(|(_, value)| value.as_str())
+            };
+            let name = element.local_name();
+            let name = String::from_utf8_lossy(name.as_ref());
+
+            let mut style = stack.last().cloned().unwrap_or_default();
+            apply_style(&mut style, &attributes, &regions)?;
+            if matches!(
+                name.as_ref(),
+                "defs" | "clipPath" | "mask" | "marker" | "pattern" | "symbol"
+            ) {
+                style.is_hidden = true;
+            }
+
+            if name == "svg" && view_box.is_none() {
+                view_box = Some(root_view_box(
+                    attribute("viewBox"),
+                    attribute("width"),
+                    attribute("height"),
+                )?);
+            } else if !style.is_hidden {
+                if let Some(path) = shape_path(&name, attribute)? {
+                    shapes.push(Shape {
+                        path,
+                        style: style.clone(),
+                    });
+                }
+            }
+
+            if !is_empty {
+                stack.push(style);
+            }
+        }
+
+        Ok(Self {
+            view_box: view_box.ok_or(IconError::NoViewBox)?,
+            shapes,
+            regions,
+            textures: None,
+        })
+    }
+
+    /// Draws the icon fitted into `rect`, filling the regions for which
+    /// `is_active` returns true. `stroke_color` and `fill_color` stand in for
+    /// `currentColor`.
+    pub fn paint(
+        &mut self,
+        painter: &egui::Painter,
+        rect: egui::Rect,
+        is_active: impl Fn(&str) -> bool,
+        stroke_color: egui::Color32,
+        fill_color: egui::Color32,
+    ) {
+        let uv = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+        for texture in self.layers(painter, rect.size(), is_active, stroke_color, fill_color) {
+            painter.image(texture, rect, uv, egui::Color32::WHITE);
+        }
+    }
+
+    /// Draws the icon like `paint` into a `rect` at least as wide as it is
+    /// tall. The artwork is drawn square at the height of `rect` and its
+    /// middle column is stretched to the width, so both ends keep their
+    /// shape. `tint` multiplies the result, such as for fading.
+    pub fn paint_stretched(
+        &mut self,
+        painter: &egui::Painter,
+        rect: egui::Rect,
+        is_active: impl Fn(&str) -> bool,
+        stroke_color: egui::Color32,
+        fill_color: egui::Color32,
+        tint: egui::Color32,
+    ) {
+        let side = rect.height();
+        let xs = [
+            rect.left(),
+            rect.left() + side / 2.0,
+            rect.right() - side / 2.0,
+            rect.right(),
+        ];
+        let us = [0.0, 0.5, 0.5, 1.0];
+        let size = egui::Vec2::splat(side);
+        for texture in self.layers(painter, size, is_active, stroke_color, fill_color) {
+            let mut mesh = egui::Mesh::with_texture(texture);
+            for (y, v) in [(rect.top(), 0.0), (rect.bottom(), 1.0)] {
+                for (x, u) in xs.into_iter().zip(us) {
+                    mesh.vertices.push(egui::epaint::Vertex {
+                        pos: egui::pos2(x, y),
+                        uv: egui::pos2(u, v),
+                        color: tint,
+                    });
+                }
+            }
+            for col in 0..3 {
+                mesh.add_triangle(col, col + 1, col + 5);
+                mesh.add_triangle(col, col + 5, col + 4);
+            }
+            painter.add(egui::Shape::mesh(mesh));
+        }
+    }
+
+    // Textures to draw for `size` on screen, bottom layer first, rendered
+    // again when the size or colors changed
+    fn layers(
+        &mut self,
+        painter: &egui::Painter,
+        size: egui::Vec2,
+        is_active: impl Fn(&str) -> bool,
+        stroke_color: egui::Color32,
+        fill_color: egui::Color32,
+    ) -> Vec<egui::TextureId> {
+        let pixels = size * painter.pixels_per_point();
+        let size = [pixels.x.round() as usize, pixels.y.round() as usize];
+        if size[0] == 0 || size[1] == 0 {
+            return Vec::new();
+        }
+
+        let key = (size, stroke_color, fill_color);
+        if self.textures.as_ref().map(|textures| textures.key) != Some(key) {
+            let render = |layer| self.render(painter.ctx(), layer, size, stroke_color, fill_color);
+            self.textures = Some(Textures {
+                key,
+                fills: render(Layer::Fills),
+                regions: self
+                    .regions
+                    .iter()
+                    .filter_map(|name| Some((name.clone(), render(Layer::Region(name))?)))
+                    .collect(),
+                strokes: render(Layer::Strokes),
+            });
+        }
+        let Some(textures) = &self.textures else {
+            return Vec::new();
+        };
+
+        let active_regions = textures
+            .regions
+            .iter()
+            .filter(|(name, _)| is_active(name))
+            .map(|(_, texture)| texture);
+        textures
+            .fills
+            .iter()
+            .chain(active_regions)
+            .chain(&textures.strokes)
+            .map(egui::TextureHandle::id)
+            .collect()
+    }
+
+    // Rasterizes the shapes of one layer, or returns None if it has none
+    fn render(
+        &self,
+        ctx: &egui::Context,
+        layer: Layer,
+        size: [usize; 2],
+        stroke_color: egui::Color32,
+        fill_color: egui::Color32,
+    ) -> Option<egui::TextureHandle> {
+        let mut pixmap = Pixmap::new(size[0] as u32, size[1] as u32)?;
+        // Fit the view box into the pixmap, centered
+        let scale =
+            (size[0] as f32 / self.view_box.width()).min(size[1] as f32 / self.view_box.height());
+        let fit = Transform::from_row(
+            scale,
+            0.0,
+            0.0,
+            scale,
+            (size[0] as f32 - self.view_box.width() * scale) / 2.0 - self.view_box.left() * scale,
+            (size[1] as f32 - self.view_box.height() * scale) / 2.0 - self.view_box.top() * scale,
+        );
+
+        let mut is_empty = true;
+        for shape in &self.shapes {
+            let style = &shape.style;
+            let transform = fit.pre_concat(style.transform);
+            let (paint, current) = match layer {
+                Layer::Strokes => (style.stroke, stroke_color),
+                Layer::Fills if style.region.is_none() => (style.fill, fill_color),
+                Layer::Region(name) if style.region.as_deref() == Some(name) => {
+                    (style.fill, fill_color)
+                }
+                _ => continue,
+            };
+            let color = match paint {
+                Paint::None => continue,
+                Paint::Current => current,
+                Paint::Color(color) => color,
+            };
+
+            let mut skia_paint = tiny_skia::Paint::default();
+            let [r, g, b, a] = color.to_srgba_unmultiplied();
+            skia_paint.set_color_rgba8(r, g, b, a);
+            skia_paint.anti_alias = true;
+            if layer == Layer::Strokes {
+                let stroke = tiny_skia::Stroke {
+                    width: style.stroke_width,
+                    line_cap: style.line_cap,
+                    line_join: style.line_join,
+                    ..Default::default()
+                };
+                pixmap.stroke_path(&shape.path, &skia_paint, &stroke, transform, None);
+            } else {
+                pixmap.fill_path(&shape.path, &skia_paint, style.fill_rule, transform, None);
+            }
+            is_empty = false;
+        }
+        if is_empty {
+            return None;
+        }
+
+        let image = egui::ColorImage::from_rgba_premultiplied(size, pixmap.data());
+        Some(ctx.load_texture("device_icon", image, Default::default()))
+    }
+}
+
+fn attributes(element: &BytesStart) -> Result<Vec<(String, String)>, IconError> {
+    element
+        .attributes()
+        .map(|attribute| -> Result<(String, String), IconError> {
+            let attribute = attribute.map_err(quick_xml::Error::from)?;
+            let key = String::from_utf8_lossy(attribute.key.local_name().as_ref()).into_owned();
+            Ok((key, attribute.unescape_value()?.into_owned()))
+        })
+        .collect()
+}
+
+fn invalid(name: &str, value: &str) -> IconError {
+    IconError::Attribute {
+        name: name.to_string(),
+        value: value.to_string(),
+    }
+}
+
+// Applies presentation attributes, then the declarations in `style`, which
+// take precedence
+fn apply_style(
+    style: &mut Style,
+    attributes: &[(String, String)],
+    regions: &[String],
+) -> Result<(), IconError> {
+    let declarations = attributes
+        .iter()
+        .filter(|(name, _)| name == "style")
+        .flat_map(|(_, value)| value.split(';'))
+        .filter_map(|declaration| declaration.split_once(':'))
+        .map(|(name, value)| (name.trim(), value.trim()));
+    let properties = attributes
+        .iter()
+        .map(|(name, value)| (name.as_str(), value.as_str()))
+        .chain(declarations);
+
+    for (name, value) in properties {
+        match name {
+            "fill" => style.fill = parse_paint(name, value)?,
+            "stroke" => style.stroke = parse_paint(name, value)?,
+            "stroke-width" => {
+                style.stroke_width = parse_length(value).ok_or_else(|| invalid(name, value))?
+            }
+            "fill-rule" => {
+                style.fill_rule = match value {
+                    "evenodd" => FillRule::EvenOdd,
+                    _ => FillRule::Winding,
+                }
+            }
+            "stroke-linecap" => {
+                style.line_cap = match value {
+                    "round" => LineCap::Round,
+                    "square" => LineCap::Square,
+                    _ => LineCap::Butt,
+                }
+            }
+            "stroke-linejoin" => {
+                style.line_join = match value {
+                    "round" => LineJoin::Round,
+                    "bevel" => LineJoin::Bevel,
+                    _ => LineJoin::Miter,
+                }
+            }
+            "transform" => {
+                style.transform = style
+                    .transform
+                    .pre_concat(parse_transform(value).ok_or_else(|| invalid(name, value))?)
+            }
+            "id" if regions.iter().any(|region| region == value) => {
+                style.region = Some(value.to_string())
+            }
+            "display" if value == "none" => style.is_hidden = true,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn parse_paint(name: &str, value: &str) -> Result<Paint, IconError> {
+    match value {
+        "none" | "transparent" => Ok(Paint::None),
+        "currentColor" => Ok(Paint::Current),
+        "black" => Ok(Paint::Color(egui::Color32::BLACK)),
+        "white" => Ok(Paint::Color(egui::Color32::WHITE)),
+        _ if value.starts_with('#') => egui::Color32::from_hex(value)
+            .map(Paint::Color)
+            .map_err(|_| invalid(name, value)),
+        _ => {
+            let channels: Vec<u8> = value
+                .strip_prefix("rgb(")
+                .and_then(|rest| rest.strip_suffix(')'))
+                .ok_or_else(|| invalid(name, value))?
+                .split(',')
+                .map(|channel| channel.trim().parse().map_err(|_| invalid(name, value)))
+                .collect::<Result<_, _>>()?;
+            match channels[..] {
+                [r, g, b] => Ok(Paint::Color(egui::Color32::from_rgb(r, g, b))),
+                _ => Err(invalid(name, value)),
+            }
+        }
+    }
+}
+
+// A number with an optional "px" unit
+fn parse_length(value: &str) -> Option<f32> {
+    value.trim().trim_end_matches("px").parse().ok()
+}
+
+fn numbers(text: &str) -> Option<Vec<f32>> {
+    let mut data = PathData::new(text);
+    let mut numbers = Vec::new();
+    while data.has_number() {
+        numbers.push(data.number()?);
+    }
+    data.is_done().then_some(numbers)
+}
+
+fn root_view_box(
+    view_box: Option<&str>,
+    width: Option<&str>,
+    height: Option<&str>,
+) -> Result<tiny_skia::Rect, IconError> {
+    if let Some(value) = view_box {
+        return match numbers(value).as_deref() {
+            Some(&[x, y, w, h]) => tiny_skia::Rect::from_xywh(x, y, w, h),
+            _ => None,
+        }
+        .ok_or_else(|| invalid("viewBox", value));
+    }
+    let width = width.and_then(parse_length).ok_or(IconError::NoViewBox)?;
+    let height = height.and_then(parse_length).ok_or(IconError::NoViewBox)?;
+    tiny_skia::Rect::from_xywh(0.0, 0.0, width, height).ok_or(IconError::NoViewBox)
+}
+
+fn parse_transform(text: &str) -> Option<Transform> {
+    let mut transform = Transform::identity();
+    let mut rest = text.trim();
+    while !rest.is_empty() {
+        let (name, after) = rest.split_once('(')?;
+        let (args, after) = after.split_once(')')?;
+        let args = numbers(args)?;
+        let next = match (name.trim(), args.as_slice()) {
+            ("matrix", &[a, b, c, d, e, f]) => Transform::from_row(a, b, c, d, e, f),
+            ("translate", &[x]) => Transform::from_translate(x, 0.0),
+            ("translate", &[x, y]) => Transform::from_translate(x, y),
+            ("scale", &[s]) => Transform::from_scale(s, s),
+            ("scale", &[x, y]) => Transform::from_scale(x, y),
+            ("rotate", &[angle]) => Transform::from_rotate(angle),
+            ("rotate", &[angle, x, y]) => Transform::from_rotate_at(angle, x, y),
+            ("skewX", &[angle]) => {
+                Transform::from_row(1.0, 0.0, angle.to_radians().tan(), 1.0, 0.0, 0.0)
+            }
+            ("skewY", &[angle]) => {
+                Transform::from_row(1.0, angle.to_radians().tan(), 0.0, 1.0, 0.0, 0.0)
+            }
+            _ => return None,
+        };
+        transform = transform.pre_concat(next);
+        rest = after.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+    }
+    Some(transform)
+}
+
+// Outline of a shape element, None for elements that draw nothing
+fn shape_path<'a>(
+    name: &str,
+    attribute: impl Fn(&str) -> Option<&'a str>,
+) -> Result<Option<tiny_skia::Path>, IconError> {
+    let number = |name: &str| -> Result<f32, IconError> {
+        match attribute(name) {
+            Some(value) => parse_length(value).ok_or_else(|| invalid(name, value)),
+            None => Ok(0.0),
+        }
+    };
+    let points = |name: &str| -> Result<Vec<f32>, IconError> {
+        let value = attribute(name).unwrap_or_default();
+        numbers(value).ok_or_else(|| invalid(name, value))
+    };
+
+    let mut builder = PathBuilder::new();
+    match name {
+        "path" => {
+            let value = attribute("d").unwrap_or_default();
+            parse_path_data(&mut builder, value).ok_or_else(|| invalid("d", value))?;
+        }
+        "rect" => {
+            let (x, y, w, h) = (
+                number("x")?,
+                number("y")?,
+                number("width")?,
+                number("height")?,
+            );
+            // A missing radius takes the other one
+            let (rx, ry) = match (attribute("rx").is_some(), attribute("ry").is_some()) {
+                (true, false) => (number("rx")?, number("rx")?),
+                (false, true) => (number("ry")?, number("ry")?),
+                _ => (number("rx")?, number("ry")?),
+            };
+            push_round_rect(&mut builder, x, y, w, h, rx.min(w / 2.0), ry.min(h / 2.0));
+        }
+        "circle" => {
+            let r = number("r")?;
+            builder.push_circle(number("cx")?, number("cy")?, r);
+        }
+        "ellipse" => {
+            let (rx, ry) = (number("rx")?, number("ry")?);
+            if let Some(oval) = tiny_skia::Rect::from_xywh(
+                number("cx")? - rx,
+                number("cy")? - ry,
+                rx * 2.0,
+                ry * 2.0,
+            ) {
+                builder.push_oval(oval);
+            }
+        }
+        "line" => {
+            builder.move_to(number("x1")?, number("y1")?);
+            builder.line_to(number("x2")?, number("y2")?);
+        }
+        "polyline" | "polygon" => {
+            for (i, point) in points("points")?.chunks_exact(2).enumerate() {
+                if i == 0 {
+                    builder.move_to(point[0], point[1]);
+                } else {
+                    builder.line_to(point[0], point[1]);
+                }
+            }
+            if name == "polygon" {
+                builder.close();
+            }
+        }
+        _ => return Ok(None),
+    }
+    Ok(builder.finish())
+}
+
+fn push_round_rect(builder: &mut PathBuilder, x: f32, y: f32, w: f32, h: f32, rx: f32, ry: f32) {
+    if rx <= 0.0 || ry <= 0.0 {
+        if let Some(rect) = tiny_skia::Rect::from_xywh(x, y, w, h) {
+            builder.push_rect(rect);
+        }
+        return;
+    }
+    let (kx, ky) = (rx * KAPPA, ry * KAPPA);
+    let (right, bottom) = (x + w, y + h);
+    builder.move_to(x + rx, y);
+    builder.line_to(right - rx, y);
+    builder.cubic_to(right - rx + kx, y, right, y + ry - ky, right, y + ry);
+    builder.line_to(right, bottom - ry);
+    builder.cubic_to(
+        right,
+        bottom - ry + ky,
+        right - rx + kx,
+        bottom,
+        right - rx,
+        bottom,
+    );
+    builder.line_to(x + rx, bottom);
+    builder.cubic_to(x + rx - kx, bottom, x, bottom - ry + ky, x, bottom - ry);
+    builder.line_to(x, y + ry);
+    builder.cubic_to(x, y + ry - ky, x + rx - kx, y, x + rx, y);
+    builder.close();
+}
+
+// Tokenizer for path data and number lists
+struct PathData<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> PathData<'a> {
+    fn new(text: &'a str) -> Self {
+        Self {
+            bytes: text.as_bytes(),
+            pos: 0,
+        }
+    }
+
+    fn skip_separators(&mut self) {
+        while self
+            .bytes
+            .get(self.pos)
+            .is_some_and(|&b| b.is_ascii_whitespace() || b == b',')
+        {
+            self.pos += 1;
+        }
+    }
+
+    fn is_done(&mut self) -> bool {
+        self.skip_separators();
+        self.pos >= self.bytes.len()
+    }
+
+    fn has_number(&mut self) -> bool {
+        self.skip_separators();
+        self.bytes
+            .get(self.pos)
+            .is_some_and(|&b| b.is_ascii_digit() || matches!(b, b'-' | b'+' | b'.'))
+    }
+
+    fn command(&mut self) -> Option<u8> {
+        self.skip_separators();
+        let byte = *self.bytes.get(self.pos)?;
+        if byte.is_ascii_alphabetic() {
+            self.pos += 1;
+            Some(byte)
+        } else {
+            None
+        }
+    }
+
+    fn number(&mut self) -> Option<f32> {
+        self.skip_separators();
+        let start = self.pos;
+        let digits = |data: &mut Self| {
+            while data.bytes.get(data.pos).is_some_and(u8::is_ascii_digit) {
+                data.pos += 1;
+            }
+        };
+        if matches!(self.bytes.get(self.pos), Some(b'-' | b'+')) {
+            self.pos += 1;
+        }
+        digits(self);
+        // A second '.' starts the next number, as in "0.5.5"
+        if self.bytes.get(self.pos) == Some(&b'.') {
+            self.pos += 1;
+            digits(self);
+        }
+        if matches!(self.bytes.get(self.pos), Some(b'e' | b'E'))
+            && self
+                .bytes
+                .get(self.pos + 1)
+                .is_some_and(|&b| b.is_ascii_digit() || b == b'-' || b == b'+')
+        {
+            self.pos += 2;
+            digits(self);
+        }
+        std::str::from_utf8(&self.bytes[start..self.pos])
+            .ok()?
+            .parse()
+            .ok()
+    }
+
+    // Arc flags may be written without separators ("a1 1 0 01 5 5")
+    fn flag(&mut self) -> Option<bool> {
+        self.skip_separators();
+        let flag = match self.bytes.get(self.pos)? {
+            b'0' => false,
+            b'1' => true,
+            _ => return None,
+        };
+        self.pos += 1;
+        Some(flag)
+    }
+}
+
+fn parse_path_data(builder: &mut PathBuilder, text: &str) -> Option<()> {
+    let mut data = PathData::new(text);
+    let mut current = (0.0, 0.0);
+    let mut start = (0.0, 0.0);
+    // Reflected control points for S and T, with the command that set them
+    let mut last_cubic: Option<(f32, f32)> = None;
+    let mut last_quad: Option<(f32, f32)> = None;
+    let mut command = data.command()?;
+
+    loop {
+        let is_relative = command.is_ascii_lowercase();
+        let kind = command.to_ascii_uppercase();
+        let offset = |(x, y): (f32, f32)| {
+            if is_relative {
+                (current.0 + x, current.1 + y)
+            } else {
+                (x, y)
+            }
+        };
+        let mut cubic = None;
+        let mut quad = None;
+
+        match kind {
+            b'M' => {
+                current = offset((data.number()?, data.number()?));
+                start = current;
+                builder.move_to(current.0, current.1);
+                // Further pairs are implicit line-tos
+                command = if is_relative { b'l' } else { b'L' };
+            }
+            b'L' => {
+                current = offset((data.number()?, data.number()?));
+                builder.line_to(current.0, current.1);
+            }
+            b'H' => {
+                let x = data.number()?;
+                current.0 = if is_relative { current.0 + x } else { x };
+                builder.line_to(current.0, current.1);
+            }
+            b'V' => {
+                let y = data.number()?;
+                current.1 = if is_relative { current.1 + y } else { y };
+                builder.line_to(current.0, current.1);
+            }
+            b'C' | b'S' => {
+                let c1 = if kind == b'C' {
+                    offset((data.number()?, data.number()?))
+                } else {
+                    reflect(last_cubic, current)
+                };
+                let c2 = offset((data.number()?, data.number()?));
+                let end = offset((data.number()?, data.number()?));
+                builder.cubic_to(c1.0, c1.1, c2.0, c2.1, end.0, end.1);
+                cubic = Some(c2);
+                current = end;
+            }
+            b'Q' | b'T' => {
+                let c = if kind == b'Q' {
+                    offset((data.number()?, data.number()?))
+                } else {
+                    reflect(last_quad, current)
+                };
+                let end = offset((data.number()?, data.number()?));
+                builder.quad_to(c.0, c.1, end.0, end.1);
+                quad = Some(c);
+                current = end;
+            }
+            b'A' => {
+                let (rx, ry, rotation) = (data.number()?, data.number()?, data.number()?);
+                let (is_large, is_sweep) = (data.flag()?, data.flag()?);
+                let end = offset((data.number()?, data.number()?));
+                arc_to(
+                    builder,
+                    current,
+                    (rx, ry),
+                    rotation,
+                    is_large,
+                    is_sweep,
+                    end,
+                );
+                current = end;
+            }
+            b'Z' => {
+                builder.close();
+                current = start;
+            }
+            _ => return None,
+        }
+        last_cubic = cubic;
+        last_quad = quad;
+
+        if data.is_done() {
+            return Some(());
+        }
+        // Numbers after a command repeat it, except after Z
+        if kind == b'Z' || !data.has_number() {
+            command = data.command()?;
+        }
+    }
+}
+
+fn reflect(control: Option<(f32, f32)>, current: (f32, f32)) -> (f32, f32) {
+    match control {
+        Some((x, y)) => (2.0 * current.0 - x, 2.0 * current.1 - y),
+        None => current,
+    }
+}
+
+// Elliptical arc as cubics of at most a quarter turn each, following the
+// endpoint-to-center conversion in the SVG specification
+fn arc_to(
+    builder: &mut PathBuilder,
+    from: (f32, f32),
+    (rx, ry): (f32, f32),
+    rotation: f32,
+    is_large: bool,
+    is_sweep: bool,
+    to: (f32, f32),
+) {
+    if from == to {
+        return;
+    }
+    let (mut rx, mut ry) = (rx.abs(), ry.abs());
+    if rx == 0.0 || ry == 0.0 {
+        builder.line_to(to.0, to.1);
+        return;
+    }
+
+    let (sin, cos) = rotation.to_radians().sin_cos();
+    let (dx, dy) = ((from.0 - to.0) / 2.0, (from.1 - to.1) / 2.0);
+    let x1 = cos * dx + sin * dy;
+    let y1 = -sin * dx + cos * dy;
+
+    // Scale up radii too small to reach the end point
+    let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+    if lambda > 1.0 {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+
+    let numerator = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+    let denominator = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+    let mut coefficient = (numerator / denominator).max(0.0).sqrt();
+    if is_large == is_sweep {
+        coefficient = -coefficient;
+    }
+    let cx1 = coefficient * rx * y1 / ry;
+    let cy1 = -coefficient * ry * x1 / rx;
+    let cx = cos * cx1 - sin * cy1 + (from.0 + to.0) / 2.0;
+    let cy = sin * cx1 + cos * cy1 + (from.1 + to.1) / 2.0;
+
+    let angle = |ux: f32, uy: f32, vx: f32, vy: f32| (ux * vy - uy * vx).atan2(ux * vx + uy * vy);
+    let (ux, uy) = ((x1 - cx1) / rx, (y1 - cy1) / ry);
+    let (vx, vy) = ((-x1 - cx1) / rx, (-y1 - cy1) / ry);
+    let start_angle = angle(1.0, 0.0, ux, uy);
+    let mut sweep = angle(ux, uy, vx, vy);
+    if !is_sweep && sweep > 0.0 {
+        sweep -= std::f32::consts::TAU;
+    } else if is_sweep && sweep < 0.0 {
+        sweep += std::f32::consts::TAU;
+    }
+
+    let segments = (sweep.abs() / std::f32::consts::FRAC_PI_2).ceil().max(1.0) as usize;
+    let step = sweep / segments as f32;
+    let handle = 4.0 / 3.0 * (step / 4.0).tan();
+    // Point on the unit circle mapped onto the ellipse
+    let map = |x: f32, y: f32| {
+        (
+            cx + rx * x * cos - ry * y * sin,
+            cy + rx * x * sin + ry * y * cos,
+        )
+    };
+    for i in 0..segments {
+        let (sin1, cos1) = (start_angle + step * i as f32).sin_cos();
+        let (sin2, cos2) = (start_angle + step * (i + 1) as f32).sin_cos();
+        let c1 = map(cos1 - handle * sin1, sin1 + handle * cos1);
+        let c2 = map(cos2 + handle * sin2, sin2 - handle * cos2);
+        // Land exactly on the end point
+        let end = if i + 1 == segments {
+            to
+        } else {
+            map(cos2, sin2)
+        };
+        builder.cubic_to(c1.0, c1.1, c2.0, c2.1, end.0, end.1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tiny_skia::PathSegment;
+
+    fn path(d: &str) -> Option<tiny_skia::Path> {
+        let mut builder = PathBuilder::new();
+        parse_path_data(&mut builder, d)?;
+        builder.finish()
+    }
+
+    // Segment kinds as SVG command letters
+    fn commands(path: &tiny_skia::Path) -> String {
+        path.segments()
+            .map(|segment| match segment {
+                PathSegment::MoveTo(_) => 'M',
+                PathSegment::LineTo(_) => 'L',
+                PathSegment::QuadTo(..) => 'Q',
+                PathSegment::CubicTo(..) => 'C',
+                PathSegment::Close => 'Z',
+            })
+            .collect()
+    }
+
+    fn points(path: &tiny_skia::Path) -> Vec<(f32, f32)> {
+        path.points().iter().map(|p| (p.x, p.y)).collect()
+    }
+
+    fn assert_near(actual: (f32, f32), expected: (f32, f32)) {
+        assert!(
+            (actual.0 - expected.0).abs() < 1e-3 && (actual.1 - expected.1).abs() < 1e-3,
+            "{actual:?} is not {expected:?}"
+        );
+    }
+
+    #[test]
+    fn repeats_implicit_commands() {
+        let cases: [(&str, &[(f32, f32)]); 4] = [
+            // Pairs after a move are line-tos, relative after a relative move
+            ("M0 0 10 0 10 10z", &[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)]),
+            ("m1 1 2 0 0 2", &[(1.0, 1.0), (3.0, 1.0), (3.0, 3.0)]),
+            ("M0 0 L1 1 2 2", &[(0.0, 0.0), (1.0, 1.0), (2.0, 2.0)]),
+            (
+                "M0 0 h5 5 v-1 -1",
+                &[
+                    (0.0, 0.0),
+                    (5.0, 0.0),
+                    (10.0, 0.0),
+                    (10.0, -1.0),
+                    (10.0, -2.0),
+                ],
+            ),
+        ];
+        for (d, expected) in cases {
+            assert_eq!(points(&path(d).unwrap()), expected, "{d}");
+        }
+
+        let curves = path("M0 0 c1 0 2 1 2 2 1 0 2 1 2 2").unwrap();
+        assert_eq!(commands(&curves), "MCC");
+        assert_eq!(points(&curves).last(), Some(&(4.0, 4.0)));
+    }
+
+    #[test]
+    fn splits_compact_numbers() {
+        let cases: [(&str, &[f32]); 5] = [
+            ("0.5.5", &[0.5, 0.5]),
+            (".5-.5", &[0.5, -0.5]),
+            ("-1-2", &[-1.0, -2.0]),
+            ("1e2-3", &[100.0, -3.0]),
+            ("1,2 3", &[1.0, 2.0, 3.0]),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(numbers(text).as_deref(), Some(expected), "{text}");
+        }
+        assert_eq!(numbers("1 x"), None);
+
+        let p = path("M0.5.5L.5-.5").unwrap();
+        assert_eq!(points(&p), [(0.5, 0.5), (0.5, -0.5)]);
+    }
+
+    #[test]
+    fn reads_compact_arc_flags() {
+        // Large arc and sweep flags run into the end point
+        let p = path("M0 0a5 5 0 1110 0").unwrap();
+        let pts = points(&p);
+        assert_near(pts[3], (5.0, -5.0));
+        assert_near(*pts.last().unwrap(), (10.0, 0.0));
+
+        // Sweep 0 goes the other way round
+        let p = path("M0 0a5 5 0 0 0 10 0").unwrap();
+        assert_near(points(&p)[3], (5.0, 5.0));
+
+        assert!(path("M0 0a5 5 0 2 0 10 0").is_none());
+    }
+
+    #[test]
+    fn arcs_follow_the_specification() {
+        // Radii too small to reach the end are scaled up to a half circle
+        let p = path("M0 0 A1 1 0 0 1 10 0").unwrap();
+        assert_near(points(&p)[3], (5.0, -5.0));
+
+        // A zero radius is a straight line, and a zero-length arc nothing
+        let p = path("M0 0 A0 5 0 0 1 10 0").unwrap();
+        assert_eq!(commands(&p), "ML");
+        let p = path("M0 0 A5 5 0 0 1 0 0 L1 1").unwrap();
+        assert_eq!(commands(&p), "ML");
+    }
+
+    #[test]
+    fn rejects_bad_path_data() {
+        for d in ["", "10 10", "M0 0 L", "M0 0 X1 2", "M0 0 L1 one"] {
+            assert!(path(d).is_none(), "{d}");
+        }
+    }
+
+    #[test]
+    fn rounds_rects_like_svg() {
+        let rect = |attributes: &[(&str, &str)]| {
+            let attribute = |name: &str| {
+                attributes
+                    .iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|&(_, value)| value)
+            };
+            shape_path("rect", attribute).unwrap().unwrap()
+        };
+        let base = [("width", "20"), ("height", "10")];
+
+        // A missing radius takes the other one
+        for radius in ["rx", "ry"] {
+            let p = rect(&[base[0], base[1], (radius, "2")]);
+            let pts = points(&p);
+            assert_eq!(pts[0], (2.0, 0.0), "{radius}");
+            assert_eq!(pts[4], (20.0, 2.0), "{radius}");
+        }
+
+        // Radii are clamped to half the side
+        let p = rect(&[base[0], base[1], ("rx", "8"), ("ry", "8")]);
+        let pts = points(&p);
+        assert_eq!(pts[0], (8.0, 0.0));
+        assert_eq!(pts[4], (20.0, 5.0));
+
+        // No radius, or a zero one, is a plain rectangle
+        for attributes in [&base[..], &[base[0], base[1], ("rx", "0"), ("ry", "3")]] {
+            let p = rect(attributes);
+            assert!(!commands(&p).contains('C'));
+            assert_eq!(
+                p.bounds(),
+                tiny_skia::Rect::from_xywh(0.0, 0.0, 20.0, 10.0).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn parses_regions_and_view_box() {
+        let icon = DeviceIcon::parse(
+            r#"<svg width="10px" height="20">
+                <g id="left"><rect width="1" height="1"/></g>
+                <defs><rect id="right" width="1" height="1"/></defs>
+                <rect id="right" width="2" height="2" style="fill: none"/>
+            </svg>"#,
+            &["left", "right"],
+        )
+        .unwrap();
+        assert_eq!(
+            icon.view_box,
+            tiny_skia::Rect::from_xywh(0.0, 0.0, 10.0, 20.0).unwrap()
+        );
+        let shapes: Vec<_> = icon
+            .shapes
+            .iter()
+            .map(|shape| (shape.style.region.as_deref(), shape.style.fill))
+            .collect();
+        assert_eq!(
+            shapes,
+            [
+                (Some("left"), Paint::Color(egui::Color32::BLACK)),
+                (Some("right"), Paint::None)
+            ]
+        );
+
+        assert!(matches!(
+            DeviceIcon::parse("<svg/>", &[]),
+            Err(IconError::NoViewBox)
+        ));
+    }
+}
@@ -1,26 +1,40 @@
 use indexmap::IndexSet;
-use std::ptr::null_mut;
+use std::{
+    ffi::c_void,
+    ptr::null_mut,
+    sync::atomic::{AtomicPtr, Ordering},
+};
+
+use crate::{key_names, locale, settings::Settings, shortcut::Shortcut};
 use windows::Win32::{
     Foundation::{LPARAM, LRESULT, WPARAM},
     System::LibraryLoader::GetModuleHandleW,
     UI::{
         Input::KeyboardAndMouse::*,
         WindowsAndMessaging::{
-            CallNextHookEx, DispatchMessageW, GetMessageW, SetWindowsHookExW, TranslateMessage,
-            HC_ACTION, HHOOK, KBDLLHOOKSTRUCT, MSG, MSLLHOOKSTRUCT, WH_KEYBOARD_LL, WH_MOUSE_LL,
+            CallNextHookEx, DispatchMessageW, GetForegroundWindow, GetMessageW,
+            GetWindowThreadProcessId, SetWindowsHookExW, TranslateMessage, HC_ACTION, HHOOK,
+            KBDLLHOOKSTRUCT, LLKHF_EXTENDED, MSG, MSLLHOOKSTRUCT, WH_KEYBOARD_LL, WH_MOUSE_LL,
         },
     },
 };
 
 static mut HOOK: HHOOK = HHOOK(null_mut());
-static mut CALLBACK: Option<Box<dyn FnMut(u32, u32) + Send>> = None;
+static mut CALLBACK: Option<Box<dyn FnMut(u32, Option<u32>, u32) + Send>> = None;
+
+// Keyboard layout of the thread owning the foreground window. The low-level
+// hook runs on our own thread, so GetKeyboardLayout(0) would report our layout
+// instead of the one the user is typing with.
+static ACTIVE_LAYOUT: AtomicPtr<c_void> = AtomicPtr::new(null_mut());
 
 static mut MOUSE_HOOK: HHOOK = HHOOK(null_mut());
 static mut MOUSE_CALLBACK: Option<Box<dyn FnMut(u32, i32, i32, u32) + Send>> = None;
 
+/// Calls `cb` with the virtual key, the physical key as a set-1 scan code and
+/// the window message of every keyboard event.
 pub unsafe fn register_hook<F>(cb: F)
 where
-    F: FnMut(u32, u32) + Send + 'static,
+    F: FnMut(u32, Option<u32>, u32) + Send + 'static,
 {
     CALLBACK = Some(Box::new(cb));
 
@@ -65,9 +79,22 @@ unsafe extern "system" fn low_level_keyboard_proc(
         let kb: &KBDLLHOOKSTRUCT = &*(l_param.0 as *const KBDLLHOOKSTRUCT);
         let msg = w_param.0 as u32;
 
+        update_active_layout();
+
+        let vk = if kb.vkCode == VK_RETURN.0 as u32 && (kb.flags & LLKHF_EXTENDED).0 != 0 {
+            // Numpad Enter shares VK_RETURN with the main Enter key
+            key_names::VK_NUMPAD_ENTER
+        } else if kb.vkCode == VK_LCONTROL.0 as u32 && kb.scanCode == 0x21D {
+            // Fake Left Ctrl sent ahead of Right Alt by AltGr layouts
+            key_names::VK_ALTGR_CTRL
+        } else {
+            kb.vkCode
+        };
+        let scan_code = hook_scan_code(kb, vk);
+
         #[allow(static_mut_refs)]
         if let Some(cb) = &mut CALLBACK {
-            cb(kb.vkCode, msg);
+            cb(vk, scan_code, msg);
         }
     }
     CallNextHookEx(HOOK, n_code, w_param, l_param)
@@ -88,88 +115,175 @@ unsafe extern "system" fn low_level_mouse_proc(
     CallNextHookEx(MOUSE_HOOK, n_code, w_param, l_param)
 }
 
-pub unsafe fn vk_to_text(vk: u32) -> String {
-    let layout = GetKeyboardLayout(0);
-    let keystate = [0u8; 256];
+/// Records the keyboard layout of the foreground window. Runs on every key
+/// event and once per frame, so a layout switched with the mouse or a change
+/// of window is picked up before the next key arrives.
+pub unsafe fn update_active_layout() {
+    let hwnd = GetForegroundWindow();
+    let thread_id = GetWindowThreadProcessId(hwnd, None);
+    let layout = GetKeyboardLayout(thread_id);
+    ACTIVE_LAYOUT.store(layout.0, Ordering::Relaxed);
+}
+
+/// Keyboard layout of the focused window as of the last key event, falling back
+/// to our own thread's layout before the first event arrives.
+pub unsafe fn active_keyboard_layout() -> HKL {
+    let layout = ACTIVE_LAYOUT.load(Ordering::Relaxed);
+    if layout.is_null() {
+        GetKeyboardLayout(0)
+    } else {
+        HKL(layout)
+    }
+}
+
+/// Character typed by `vk` with the given keys held on `layout`. Dead keys and
+/// non-printing keys yield `None`.
+pub unsafe fn vk_to_char(vk: u32, keys: &IndexSet<u32>, layout: HKL) -> Option<String> {
+    // Don't let ToUnicodeEx consume pending dead-key state
+    const TOUNICODE_NO_STATE_CHANGE: u32 = 0x4;
+
+    let mut keystate = [0u8; 256];
+    for &key in keys {
+        let key = if key == key_names::VK_ALTGR_CTRL {
+            VK_LCONTROL.0 as u32
+        } else {
+            key
+        };
+        if key < 0x100 {
+            keystate[key as usize] = 0x80;
+        }
+    }
+    // ToUnicodeEx reads the generic modifier slots
+    for (generic, left, right) in [
+        (VK_SHIFT, VK_LSHIFT, VK_RSHIFT),
+        (VK_CONTROL, VK_LCONTROL, VK_RCONTROL),
+        (VK_MENU, VK_LMENU, VK_RMENU),
+    ] {
+        keystate[generic.0 as usize] |= keystate[left.0 as usize] | keystate[right.0 as usize];
+    }
+    keystate[VK_CAPITAL.0 as usize] = (GetKeyState(VK_CAPITAL.0 as i32) & 1) as u8;
+
     let mut buf = [0u16; 8];
+    let rc = ToUnicodeEx(
+        vk,
+        0,
+        &keystate,
+        &mut buf,
+        TOUNICODE_NO_STATE_CHANGE,
+        layout,
+    );
+    if rc <= 0 {
+        return None;
+    }
+
+    let text = String::from_utf16_lossy(&buf[..rc as usize]);
+    if text.chars().any(char::is_control) {
+        None
+    } else {
+        Some(text)
+    }
+}
 
+// Physical key of a hook event as a set-1 scan code with 0xE0 in the high byte
+// for extended keys. The hook's own scan code tells keys that share a virtual
+// key apart, such as numpad 7 and Home while NumLock is off.
+unsafe fn hook_scan_code(kb: &KBDLLHOOKSTRUCT, vk: u32) -> Option<u32> {
+    let is_extended = (kb.flags & LLKHF_EXTENDED).0 != 0;
     match vk {
-        0x08 => "Backspace",
-        0x09 => "Tab",
-        0x0D => "Enter",
-        0x13 => "Pause",
-        0x14 => "CapsLock",
-        0x1B => "Esc",
-        0x20 => "Space",
-        0x21 => "PageUp",
-        0x22 => "PageDown",
-        0x23 => "End",
-        0x24 => "Home",
-        0x25 => "⬅",
-        0x26 => "⬆",
-        0x27 => "➡",
-        0x28 => "⬇",
-        0x2C => "PrintScreen",
-        0x2D => "Insert",
-        0x2E => "Delete",
-        0x90 => "NumLock",
-        0x91 => "ScrollLock",
-
-        // Modifier keys
-        0x10 => "Shift",
-        0xA0 => "Shift",
-        0xA1 => "Shift",
-
-        0x11 => "Ctrl",
-        0xA2 => "Ctrl", // Left Ctrl
-        0xA3 => "Ctrl", // Right Ctrl
-
-        0x12 => "Alt",
-        0xA4 => "Alt", // Left Alt
-        0xA5 => "Alt", // Right Alt
-
-        0x5B => "Win", // Left Windows
-        0x5C => "Win", // Right Windows
-
-        0x5D => "Apps",
-
-        0x15 => "Kana",  // VK_KANA
-        0x19 => "Kanji", // VK_KANJI
-
-        0x70..=0x7B => {
-            let n = vk - 0x6F; // F1..F12
-            return format!("F{n}");
+        // Not a key of its own
+        key_names::VK_ALTGR_CTRL => None,
+        // Pause arrives as 0x45 like NumLock, and NumLock is flagged extended
+        0x13 => Some(0xE11D),
+        0x90 => Some(0x45),
+        // Injected input often carries no scan code
+        _ if kb.scanCode == 0 => vk_to_scan_code(vk),
+        _ if is_extended => Some(0xE000 | (kb.scanCode & 0xFF)),
+        _ => Some(kb.scanCode & 0xFF),
+    }
+}
+
+// Physical key of `vk` on the focused window's layout, in the same form. Keys
+// that share a virtual key resolve to just one of them.
+unsafe fn vk_to_scan_code(vk: u32) -> Option<u32> {
+    match vk {
+        key_names::VK_NUMPAD_ENTER => Some(0xE01C),
+        // Pause sends E1 1D 45, which has no VSC_EX form
+        0x13 => Some(0xE11D),
+        _ if vk > 0xFF => None,
+        _ => match MapVirtualKeyExW(vk, MAPVK_VK_TO_VSC_EX, active_keyboard_layout()) {
+            0 => None,
+            code => Some(code),
+        },
+    }
+}
+
+pub unsafe fn vk_to_text(vk: u32, sided: bool, layout: HKL) -> String {
+    const LANG_KOREAN: usize = 0x12;
+
+    if sided {
+        if let Some(name) = key_names::sided_vk_name(vk) {
+            return name.to_string();
         }
-        _ => {
-            let rc = ToUnicodeEx(vk, 0, &keystate, &mut buf, 0, layout);
-            if rc > 0 {
-                return String::from_utf16_lossy(&buf[..rc as usize]).to_uppercase();
-            } else {
-                return format!("VK_{vk:02X}");
-            }
+    }
+
+    let keystate = [0u8; 256];
+    let mut buf = [0u16; 8];
+
+    // PRIMARYLANGID of the layout's language identifier
+    if (layout.0 as usize & 0x3FF) == LANG_KOREAN {
+        if let Some(name) = key_names::korean_ime_name(vk) {
+            return name.to_string();
         }
     }
-    .to_string()
+
+    if let Some(name) = key_names::vk_name(vk) {
+        return name.to_string();
+    }
+
+    let rc = ToUnicodeEx(vk, 0, &keystate, &mut buf, 0, layout);
+    if rc > 0 {
+        String::from_utf16_lossy(&buf[..rc as usize]).to_uppercase()
+    } else if let Some(name) = key_names::oem_name(vk) {
+        name.to_string()
+    } else {
+        format!("VK_{vk:02X}")
+    }
 }
 
-pub fn key_combination_to_string(keys: &mut IndexSet<u32>) -> String {
-    let modifier_priority = |vk: u32| -> u16 {
-        match VIRTUAL_KEY(vk as u16) {
-            VK_LCONTROL | VK_RCONTROL => 0, // Ctrl
-            VK_LSHIFT | VK_RSHIFT => 1,     // Shift
-            VK_LMENU | VK_RMENU => 2,       // Alt
-            VK_LWIN | VK_RWIN => 3,         // Meta(Win/Super)
-            _ => 10,
-        }
-    };
+/// Labels for each key of a combination in canonical `Shortcut` order
+/// (modifiers first, in the configured order), with key names in the
+/// configured language and characters from `layout`, the keyboard layout the
+/// combination was typed with.
+///
+/// With sided modifiers enabled, left and right modifiers keep their own names,
+/// and an AltGr press (synthesized LCtrl + RAlt) is shown as a single "AltGr"
+/// key.
+pub fn key_combination_labels(
+    keys: &mut IndexSet<u32>,
+    layout: HKL,
+    settings: &Settings,
+) -> Vec<(u32, String)> {
+    let sided = settings.is_sided_modifiers;
+    let locale = settings.locale();
+
+    let shortcut = Shortcut::from_keys(keys.iter(), &settings.modifier_order);
+    keys.sort_by_key(|&vk| shortcut.position(vk));
 
-    keys.sort_by_key(|&vk| modifier_priority(vk));
+    let is_altgr = sided && keys.contains(&key_names::VK_ALTGR_CTRL);
 
     unsafe {
         keys.iter()
-            .map(|&vk| vk_to_text(vk))
-            .collect::<Vec<_>>()
-            .join(" + ")
+            .filter(|&&vk| !(is_altgr && vk == key_names::VK_ALTGR_CTRL))
+            .map(|&vk| {
+                let label = if is_altgr && vk == VK_RMENU.0 as u32 {
+                    "AltGr".to_string()
+                } else {
+                    let text = vk_to_text(vk, sided, layout);
+                    locale::key_name(locale, &text).to_string()
+                };
+                (vk, label)
+            })
+            .collect()
     }
 }
 
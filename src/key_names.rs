@@ -0,0 +1,694 @@
+// Key-name database for the Windows virtual-key space, plus the Linux evdev
+// and X11 keysym spaces.
+//
+// Layout-dependent keys (letters, digits, OEM punctuation) are intentionally
+// left out: `vk_to_text` resolves them through `ToUnicodeEx` so they follow the
+// active keyboard layout, and only falls back to `oem_name` when that fails.
+//
+// Fn is handled in keyboard firmware on most machines and never reaches
+// Windows, so it has no virtual key. Linux reports it as an evdev code on
+// keyboards that pass it through, and `evdev_name` names it.
+
+/// Pseudo virtual key for the numpad Enter key. Windows reports it as
+/// `VK_RETURN` with the extended flag set, so the hook remaps it to a value
+/// outside the 8-bit VK range.
+pub const VK_NUMPAD_ENTER: u32 = 0x100 | 0x0D;
+
+/// Pseudo virtual key for the Left Ctrl press that Windows synthesizes in
+/// front of Right Alt on layouts with AltGr (AltGr = LCtrl + RAlt).
+pub const VK_ALTGR_CTRL: u32 = 0x100 | 0xA2;
+
+/// Pseudo virtual keys for mouse buttons that moved past the drag threshold
+/// while held, indexed like the mouse button state (left, right, middle, X1, X2).
+pub const VK_MOUSE_DRAG: [u32; 5] = [0x101, 0x102, 0x104, 0x105, 0x106];
+
+/// Mouse button virtual keys, indexed like the mouse button state.
+pub const VK_MOUSE_BUTTON: [u32; 5] = [0x01, 0x02, 0x04, 0x05, 0x06];
+
+/// Pseudo virtual keys for one wheel notch in each direction.
+pub const VK_WHEEL_UP: u32 = 0x180;
+pub const VK_WHEEL_DOWN: u32 = 0x181;
+pub const VK_WHEEL_LEFT: u32 = 0x182;
+pub const VK_WHEEL_RIGHT: u32 = 0x183;
+
+pub fn is_mouse_key(vk: u32) -> bool {
+    VK_MOUSE_BUTTON.contains(&vk)
+        || VK_MOUSE_DRAG.contains(&vk)
+        || (VK_WHEEL_UP..=VK_WHEEL_RIGHT).contains(&vk)
+}
+
+pub fn vk_name(vk: u32) -> Option<&'static str> {
+    let name = match vk {
+        // Mouse buttons, fed in by the mouse hook
+        0x01 => "Click",
+        0x02 => "Right Click",
+        0x03 => "Break",
+        0x04 => "Middle Click",
+        0x05 => "Mouse 4",
+        0x06 => "Mouse 5",
+        0x101 => "Drag",
+        0x102 => "Right Drag",
+        0x104 => "Middle Drag",
+        0x105 => "Mouse 4 Drag",
+        0x106 => "Mouse 5 Drag",
+        VK_WHEEL_UP => "Scroll ⬆",
+        VK_WHEEL_DOWN => "Scroll ⬇",
+        VK_WHEEL_LEFT => "Scroll ⬅",
+        VK_WHEEL_RIGHT => "Scroll ➡",
+
+        // Editing and navigation
+        0x08 => "Backspace",
+        0x09 => "Tab",
+        0x0C => "Clear",
+        0x0D => "Enter",
+        0x13 => "Pause",
+        0x14 => "CapsLock",
+        0x1B => "Esc",
+        0x20 => "Space",
+        0x21 => "PageUp",
+        0x22 => "PageDown",
+        0x23 => "End",
+        0x24 => "Home",
+        0x25 => "⬅",
+        0x26 => "⬆",
+        0x27 => "➡",
+        0x28 => "⬇",
+        0x29 => "Select",
+        0x2A => "Print",
+        0x2B => "Execute",
+        0x2C => "PrintScreen",
+        0x2D => "Insert",
+        0x2E => "Delete",
+        0x2F => "Help",
+        0x90 => "NumLock",
+        0x91 => "ScrollLock",
+
+        // Modifier keys
+        0x10 => "Shift",
+        0xA0 => "Shift", // Left Shift
+        0xA1 => "Shift", // Right Shift
+
+        0x11 => "Ctrl",
+        0xA2 => "Ctrl", // Left Ctrl
+        0xA3 => "Ctrl", // Right Ctrl
+        VK_ALTGR_CTRL => "Ctrl",
+
+        0x12 => "Alt",
+        0xA4 => "Alt", // Left Alt
+        0xA5 => "Alt", // Right Alt
+
+        0x5B => "Win", // Left Windows
+        0x5C => "Win", // Right Windows
+
+        0x5D => "Apps",
+        0x5F => "Sleep",
+
+        // IME keys. 0x15 and 0x19 are shared between Japanese and Korean
+        // layouts; `korean_ime_name` picks the right label for the active language.
+        0x15 => "Kana",
+        0x16 => "IME On",
+        0x17 => "Junja",
+        0x18 => "Final",
+        0x19 => "Kanji",
+        0x1A => "IME Off",
+        0x1C => "Convert",
+        0x1D => "NonConvert",
+        0x1E => "Accept",
+        0x1F => "ModeChange",
+        0xE5 => "Process",
+        0xE7 => "Packet",
+
+        // Numpad
+        0x60 => "Num 0",
+        0x61 => "Num 1",
+        0x62 => "Num 2",
+        0x63 => "Num 3",
+        0x64 => "Num 4",
+        0x65 => "Num 5",
+        0x66 => "Num 6",
+        0x67 => "Num 7",
+        0x68 => "Num 8",
+        0x69 => "Num 9",
+        0x6A => "Num *",
+        0x6B => "Num +",
+        0x6C => "Num ,",
+        0x6D => "Num -",
+        0x6E => "Num .",
+        0x6F => "Num /",
+        VK_NUMPAD_ENTER => "Num Enter",
+
+        // Function keys F1..F24
+        0x70 => "F1",
+        0x71 => "F2",
+        0x72 => "F3",
+        0x73 => "F4",
+        0x74 => "F5",
+        0x75 => "F6",
+        0x76 => "F7",
+        0x77 => "F8",
+        0x78 => "F9",
+        0x79 => "F10",
+        0x7A => "F11",
+        0x7B => "F12",
+        0x7C => "F13",
+        0x7D => "F14",
+        0x7E => "F15",
+        0x7F => "F16",
+        0x80 => "F17",
+        0x81 => "F18",
+        0x82 => "F19",
+        0x83 => "F20",
+        0x84 => "F21",
+        0x85 => "F22",
+        0x86 => "F23",
+        0x87 => "F24",
+
+        // Browser keys
+        0xA6 => "Browser Back",
+        0xA7 => "Browser Forward",
+        0xA8 => "Browser Refresh",
+        0xA9 => "Browser Stop",
+        0xAA => "Browser Search",
+        0xAB => "Browser Favorites",
+        0xAC => "Browser Home",
+
+        // Volume and media keys
+        0xAD => "Mute",
+        0xAE => "Volume Down",
+        0xAF => "Volume Up",
+        0xB0 => "Next Track",
+        0xB1 => "Prev Track",
+        0xB2 => "Stop",
+        0xB3 => "Play/Pause",
+
+        // Launch keys
+        0xB4 => "Mail",
+        0xB5 => "Media Select",
+        0xB6 => "App 1",
+        0xB7 => "App 2",
+
+        // Gamepad keys (Xbox controllers routed through the keyboard stack)
+        0xC3 => "Pad A",
+        0xC4 => "Pad B",
+        0xC5 => "Pad X",
+        0xC6 => "Pad Y",
+        0xC7 => "Pad RB",
+        0xC8 => "Pad LB",
+        0xC9 => "Pad LT",
+        0xCA => "Pad RT",
+        0xCB => "Pad ⬆",
+        0xCC => "Pad ⬇",
+        0xCD => "Pad ⬅",
+        0xCE => "Pad ➡",
+        0xCF => "Pad Menu",
+        0xD0 => "Pad View",
+        0xD1 => "Pad LS Button",
+        0xD2 => "Pad RS Button",
+        0xD3 => "Pad LS ⬆",
+        0xD4 => "Pad LS ⬇",
+        0xD5 => "Pad LS ➡",
+        0xD6 => "Pad LS ⬅",
+        0xD7 => "Pad RS ⬆",
+        0xD8 => "Pad RS ⬇",
+        0xD9 => "Pad RS ➡",
+        0xDA => "Pad RS ⬅",
+
+        // Legacy terminal keys
+        0xF6 => "Attn",
+        0xF7 => "CrSel",
+        0xF8 => "ExSel",
+        0xF9 => "EraseEOF",
+        0xFA => "Play",
+        0xFB => "Zoom",
+        0xFD => "PA1",
+        0xFE => "OEM Clear",
+
+        _ => return None,
+    };
+    Some(name)
+}
+
+/// Label for modifiers when left/right distinction is enabled.
+pub fn sided_vk_name(vk: u32) -> Option<&'static str> {
+    let name = match vk {
+        0xA0 => "LShift",
+        0xA1 => "RShift",
+        0xA2 => "LCtrl",
+        0xA3 => "RCtrl",
+        0xA4 => "LAlt",
+        0xA5 => "RAlt",
+        0x5B => "LWin",
+        0x5C => "RWin",
+        _ => return None,
+    };
+    Some(name)
+}
+
+/// Label for the shared IME virtual keys on a Korean layout.
+pub fn korean_ime_name(vk: u32) -> Option<&'static str> {
+    match vk {
+        0x15 => Some("Hangul"),
+        0x19 => Some("Hanja"),
+        _ => None,
+    }
+}
+
+/// Fallback label for OEM keys when the active layout has no character for
+/// them (dead keys, unassigned positions).
+pub fn oem_name(vk: u32) -> Option<&'static str> {
+    let name = match vk {
+        0xBA => "OEM_1",
+        0xBB => "OEM_Plus",
+        0xBC => "OEM_Comma",
+        0xBD => "OEM_Minus",
+        0xBE => "OEM_Period",
+        0xBF => "OEM_2",
+        0xC0 => "OEM_3",
+        0xDB => "OEM_4",
+        0xDC => "OEM_5",
+        0xDD => "OEM_6",
+        0xDE => "OEM_7",
+        0xDF => "OEM_8",
+        0xE1 => "OEM_AX",
+        0xE2 => "OEM_102",
+        0xE3 => "ICO_Help",
+        0xE4 => "ICO_00",
+        0xE6 => "ICO_Clear",
+        // Nokia/Ericsson terminal keys
+        0xE9 => "OEM_Reset",
+        0xEA => "OEM_Jump",
+        0xEB => "OEM_PA1",
+        0xEC => "OEM_PA2",
+        0xED => "OEM_PA3",
+        0xEE => "OEM_WsCtrl",
+        0xEF => "OEM_CuSel",
+        0xF0 => "OEM_Attn",
+        0xF1 => "OEM_Finish",
+        0xF2 => "OEM_Copy",
+        0xF3 => "OEM_Auto",
+        0xF4 => "OEM_EnlW",
+        0xF5 => "OEM_BackTab",
+        _ => return None,
+    };
+    Some(name)
+}
+
+/// Name of a Linux evdev key code (`KEY_*` in linux/input-event-codes.h), for
+/// the keys whose label doesn't depend on the layout.
+#[cfg(target_os = "linux")]
+pub fn evdev_name(code: u16) -> Option<&'static str> {
+    let name = match code {
+        // Editing and navigation
+        1 => "Esc",
+        14 => "Backspace",
+        15 => "Tab",
+        28 => "Enter",
+        57 => "Space",
+        58 => "CapsLock",
+        69 => "NumLock",
+        70 => "ScrollLock",
+        99 => "PrintScreen",
+        102 => "Home",
+        103 => "⬆",
+        104 => "PageUp",
+        105 => "⬅",
+        106 => "➡",
+        107 => "End",
+        108 => "⬇",
+        109 => "PageDown",
+        110 => "Insert",
+        111 => "Delete",
+        119 => "Pause",
+        127 => "Compose",
+        138 => "Help",
+        139 => "Menu",
+        142 => "Sleep",
+
+        // Modifier keys
+        29 | 97 => "Ctrl",
+        42 | 54 => "Shift",
+        56 | 100 => "Alt",
+        125 | 126 => "Super",
+        0x1D0 => "Fn",
+        0x1D1 => "Fn Esc",
+
+        // IME keys
+        85 => "Zenkaku/Hankaku",
+        89 => "Ro",
+        90 => "Katakana",
+        91 => "Hiragana",
+        92 => "Henkan",
+        93 => "Katakana/Hiragana",
+        94 => "Muhenkan",
+        122 => "Hangul",
+        123 => "Hanja",
+
+        // Numpad
+        82 => "Num 0",
+        79 => "Num 1",
+        80 => "Num 2",
+        81 => "Num 3",
+        75 => "Num 4",
+        76 => "Num 5",
+        77 => "Num 6",
+        71 => "Num 7",
+        72 => "Num 8",
+        73 => "Num 9",
+        55 => "Num *",
+        78 => "Num +",
+        121 => "Num ,",
+        74 => "Num -",
+        83 => "Num .",
+        98 => "Num /",
+        117 => "Num =",
+        96 => "Num Enter",
+
+        // Function keys F1..F24
+        59 => "F1",
+        60 => "F2",
+        61 => "F3",
+        62 => "F4",
+        63 => "F5",
+        64 => "F6",
+        65 => "F7",
+        66 => "F8",
+        67 => "F9",
+        68 => "F10",
+        87 => "F11",
+        88 => "F12",
+        183 => "F13",
+        184 => "F14",
+        185 => "F15",
+        186 => "F16",
+        187 => "F17",
+        188 => "F18",
+        189 => "F19",
+        190 => "F20",
+        191 => "F21",
+        192 => "F22",
+        193 => "F23",
+        194 => "F24",
+
+        // Browser keys
+        158 => "Browser Back",
+        159 => "Browser Forward",
+        173 => "Browser Refresh",
+        128 => "Browser Stop",
+        217 => "Browser Search",
+        156 => "Browser Favorites",
+        172 => "Browser Home",
+
+        // Volume and media keys
+        113 => "Mute",
+        114 => "Volume Down",
+        115 => "Volume Up",
+        163 => "Next Track",
+        165 => "Prev Track",
+        166 => "Stop",
+        164 => "Play/Pause",
+
+        // Launch keys
+        155 => "Mail",
+        226 => "Media Select",
+        157 => "App 1",
+        140 => "App 2",
+
+        _ => return None,
+    };
+    Some(name)
+}
+
+/// Name of an X11 keysym that doesn't stand for a character, such as
+/// `XK_Return` or the `XF86XK_*` media keys. Character keysyms are left to the
+/// layout like the layout-dependent virtual keys.
+#[cfg(target_os = "linux")]
+pub fn keysym_name(keysym: u32) -> Option<&'static str> {
+    let name = match keysym {
+        // Editing and navigation
+        0x0020 => "Space",
+        0xFF08 => "Backspace",
+        0xFF09 => "Tab",
+        0xFF0B => "Clear",
+        0xFF0D => "Enter",
+        0xFF13 => "Pause",
+        0xFF14 => "ScrollLock",
+        0xFF15 => "SysRq",
+        0xFF1B => "Esc",
+        0xFF20 => "Compose",
+        0xFF50 | 0xFF95 => "Home",
+        0xFF51 | 0xFF96 => "⬅",
+        0xFF52 | 0xFF97 => "⬆",
+        0xFF53 | 0xFF98 => "➡",
+        0xFF54 | 0xFF99 => "⬇",
+        0xFF55 | 0xFF9A => "PageUp",
+        0xFF56 | 0xFF9B => "PageDown",
+        0xFF57 | 0xFF9C => "End",
+        0xFF60 => "Select",
+        0xFF61 => "PrintScreen",
+        0xFF62 => "Execute",
+        0xFF63 | 0xFF9E => "Insert",
+        0xFF67 => "Menu",
+        0xFF68 => "Find",
+        0xFF6A => "Help",
+        0xFF6B => "Break",
+        0xFF7F => "NumLock",
+        0xFFE5 => "CapsLock",
+        0xFFFF | 0xFF9F => "Delete",
+
+        // Modifier keys
+        0xFFE1 | 0xFFE2 => "Shift",
+        0xFFE3 | 0xFFE4 => "Ctrl",
+        0xFFE7 | 0xFFE8 => "Meta",
+        0xFFE9 | 0xFFEA => "Alt",
+        0xFFEB | 0xFFEC => "Super",
+        0xFE03 => "AltGr",
+        0xFF7E => "ModeChange",
+
+        // IME keys
+        0xFF21 => "Kanji",
+        0xFF22 => "Muhenkan",
+        0xFF23 => "Henkan",
+        0xFF27 => "Hiragana/Katakana",
+        0xFF2A => "Zenkaku/Hankaku",
+        0xFF31 => "Hangul",
+        0xFF34 => "Hanja",
+
+        // Numpad, with NumLock on; with it off the keys send the navigation
+        // keysyms above
+        0xFFB0 => "Num 0",
+        0xFFB1 => "Num 1",
+        0xFFB2 => "Num 2",
+        0xFFB3 => "Num 3",
+        0xFFB4 => "Num 4",
+        0xFFB5 => "Num 5",
+        0xFFB6 => "Num 6",
+        0xFFB7 => "Num 7",
+        0xFFB8 => "Num 8",
+        0xFFB9 => "Num 9",
+        0xFF9D => "Num 5", // KP_Begin, 5 with NumLock off
+        0xFFAA => "Num *",
+        0xFFAB => "Num +",
+        0xFFAC => "Num ,",
+        0xFFAD => "Num -",
+        0xFFAE => "Num .",
+        0xFFAF => "Num /",
+        0xFFBD => "Num =",
+        0xFF8D => "Num Enter",
+
+        // Function keys F1..F24
+        0xFFBE => "F1",
+        0xFFBF => "F2",
+        0xFFC0 => "F3",
+        0xFFC1 => "F4",
+        0xFFC2 => "F5",
+        0xFFC3 => "F6",
+        0xFFC4 => "F7",
+        0xFFC5 => "F8",
+        0xFFC6 => "F9",
+        0xFFC7 => "F10",
+        0xFFC8 => "F11",
+        0xFFC9 => "F12",
+        0xFFCA => "F13",
+        0xFFCB => "F14",
+        0xFFCC => "F15",
+        0xFFCD => "F16",
+        0xFFCE => "F17",
+        0xFFCF => "F18",
+        0xFFD0 => "F19",
+        0xFFD1 => "F20",
+        0xFFD2 => "F21",
+        0xFFD3 => "F22",
+        0xFFD4 => "F23",
+        0xFFD5 => "F24",
+
+        // Browser keys
+        0x1008FF26 => "Browser Back",
+        0x1008FF27 => "Browser Forward",
+        0x1008FF29 => "Browser Refresh",
+        0x1008FF28 => "Browser Stop",
+        0x1008FF1B => "Browser Search",
+        0x1008FF30 => "Browser Favorites",
+        0x1008FF18 => "Browser Home",
+
+        // Volume and media keys
+        0x1008FF12 => "Mute",
+        0x1008FF11 => "Volume Down",
+        0x1008FF13 => "Volume Up",
+        0x1008FF17 => "Next Track",
+        0x1008FF16 => "Prev Track",
+        0x1008FF15 => "Stop",
+        0x1008FF14 | 0x1008FF31 => "Play/Pause",
+
+        // Launch keys
+        0x1008FF19 => "Mail",
+        0x1008FF32 => "Media Select",
+        0x1008FF33 => "App 1",
+        0x1008FF1D => "App 2",
+        0x1008FF2F => "Sleep",
+
+        _ => return None,
+    };
+    Some(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_virtual_keys() {
+        let cases = [
+            (0x65, "Num 5"),
+            (0x6A, "Num *"),
+            (0x6E, "Num ."),
+            (VK_NUMPAD_ENTER, "Num Enter"),
+            (0x0D, "Enter"),
+            (0xAD, "Mute"),
+            (0xAF, "Volume Up"),
+            (0xB3, "Play/Pause"),
+            (0xB1, "Prev Track"),
+            (0xA6, "Browser Back"),
+            (0xAC, "Browser Home"),
+            (0xB4, "Mail"),
+            (0xB5, "Media Select"),
+            (0xB6, "App 1"),
+            (0xB7, "App 2"),
+            (0x7B, "F12"),
+            (0x7C, "F13"),
+            (0x80, "F17"),
+            (0x87, "F24"),
+            (0x15, "Kana"),
+            (0x1C, "Convert"),
+            (0xE5, "Process"),
+            (0xA3, "Ctrl"),
+            (VK_ALTGR_CTRL, "Ctrl"),
+            (VK_WHEEL_DOWN, "Scroll ⬇"),
+        ];
+        for (vk, name) in cases {
+            assert_eq!(vk_name(vk), Some(name), "VK {vk:#X}");
+        }
+    }
+
+    #[test]
+    fn leaves_layout_keys_unnamed() {
+        // Letters, digits and OEM punctuation come from the keyboard layout
+        for vk in [0x30, 0x39, 0x41, 0x5A, 0xBA, 0xBC, 0xDE] {
+            assert_eq!(vk_name(vk), None, "VK {vk:#X}");
+        }
+    }
+
+    #[test]
+    fn names_sided_modifiers() {
+        let cases = [
+            (0xA0, Some("LShift")),
+            (0xA1, Some("RShift")),
+            (0xA2, Some("LCtrl")),
+            (0xA5, Some("RAlt")),
+            (0x5C, Some("RWin")),
+            (0x10, None),
+            (0x11, None),
+        ];
+        for (vk, name) in cases {
+            assert_eq!(sided_vk_name(vk), name, "VK {vk:#X}");
+        }
+    }
+
+    #[test]
+    fn names_oem_and_ime_keys() {
+        let oem = [
+            (0xBB, Some("OEM_Plus")),
+            (0xE2, Some("OEM_102")),
+            (0xE4, Some("ICO_00")),
+            (0xE9, Some("OEM_Reset")),
+            (0xF5, Some("OEM_BackTab")),
+            (0xF6, None),
+        ];
+        for (vk, name) in oem {
+            assert_eq!(oem_name(vk), name, "VK {vk:#X}");
+        }
+        let ime = [(0x15, Some("Hangul")), (0x19, Some("Hanja")), (0x1C, None)];
+        for (vk, name) in ime {
+            assert_eq!(korean_ime_name(vk), name, "VK {vk:#X}");
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn names_evdev_codes() {
+        let cases = [
+            (76, Some("Num 5")),
+            (55, Some("Num *")),
+            (96, Some("Num Enter")),
+            (28, Some("Enter")),
+            (113, Some("Mute")),
+            (115, Some("Volume Up")),
+            (164, Some("Play/Pause")),
+            (158, Some("Browser Back")),
+            (172, Some("Browser Home")),
+            (155, Some("Mail")),
+            (140, Some("App 2")),
+            (88, Some("F12")),
+            (183, Some("F13")),
+            (194, Some("F24")),
+            (122, Some("Hangul")),
+            (92, Some("Henkan")),
+            (97, Some("Ctrl")),
+            (0x1D0, Some("Fn")),
+            // KEY_A, KEY_1 and KEY_102ND follow the layout
+            (30, None),
+            (2, None),
+            (86, None),
+        ];
+        for (code, name) in cases {
+            assert_eq!(evdev_name(code), name, "evdev {code}");
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn names_keysyms() {
+        let cases = [
+            (0xFFB5, Some("Num 5")),
+            (0xFF9D, Some("Num 5")),
+            (0xFF8D, Some("Num Enter")),
+            (0xFF0D, Some("Enter")),
+            (0x1008FF12, Some("Mute")),
+            (0x1008FF13, Some("Volume Up")),
+            (0x1008FF14, Some("Play/Pause")),
+            (0x1008FF26, Some("Browser Back")),
+            (0x1008FF19, Some("Mail")),
+            (0x1008FF1D, Some("App 2")),
+            (0xFFCA, Some("F13")),
+            (0xFFD5, Some("F24")),
+            (0xFF31, Some("Hangul")),
+            (0xFE03, Some("AltGr")),
+            // `a`, `A` and `adiaeresis` are characters
+            (0x61, None),
+            (0x41, None),
+            (0xE4, None),
+        ];
+        for (keysym, name) in cases {
+            assert_eq!(keysym_name(keysym), name, "keysym {keysym:#X}");
+        }
+    }
+}
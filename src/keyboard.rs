@@ -0,0 +1,578 @@
+// On-screen keyboard view: a KLE layout drawn key by key, lighting the keys
+// that are held.
+//
+// Layout positions map to physical keys by scan code, so the diagram follows
+// the switch that was pressed whatever the active keyboard layout. Each key
+// takes the scan code of the first legend naming a key on a US keyboard
+// ("Q", "Enter", "PgUp"). Keys whose legends don't name one, such as the
+// extra ISO and JIS keys, can carry an "SC_xx" legend with the hexadecimal
+// scan code; it is used for mapping only and not drawn. Unsided modifier
+// legends map to the left key the first time and to the right key after that.
+//
+// Numpad keys are usually labelled like the main row ("7", "+", "Enter"), so
+// those legends name the numpad key when the key lies right of the main block
+// of letters and punctuation. Legends such as "Num 7" name it anywhere.
+
+use std::{collections::HashSet, fmt, fs, io, path::Path};
+
+use eframe::egui;
+
+use crate::{
+    key_names::VK_NUMPAD_ENTER,
+    kle::{self, KleKey, ParseKleError},
+    shortcut::{Modifier, Shortcut, Side},
+};
+
+// Largest key size in points; smaller when the window is narrow
+const MAX_KEY_SIZE: f32 = 36.0;
+
+// Legends that aren't key names elsewhere
+const LEGEND_ALIASES: &[(&str, u32)] = &[("↑", 0x26), ("↓", 0x28), ("←", 0x25), ("→", 0x27)];
+
+// Legends that name a numpad key on keys right of the main block
+const NUMPAD_LEGENDS: &[(&str, u32)] = &[
+    ("0", 0x60),
+    ("1", 0x61),
+    ("2", 0x62),
+    ("3", 0x63),
+    ("4", 0x64),
+    ("5", 0x65),
+    ("6", 0x66),
+    ("7", 0x67),
+    ("8", 0x68),
+    ("9", 0x69),
+    ("*", 0x6A),
+    ("+", 0x6B),
+    ("-", 0x6D),
+    (".", 0x6E),
+    ("/", 0x6F),
+    ("Enter", VK_NUMPAD_ENTER),
+];
+
+// Set-1 scan codes of the keys on a US keyboard, with 0xE0 in the high byte
+// for extended keys. Pause sends E1 1D 45 and is listed as 0xE11D.
+const US_SCAN_CODES: &[(u32, u32)] = &[
+    (0x1B, 0x01),
+    (0x31, 0x02),
+    (0x32, 0x03),
+    (0x33, 0x04),
+    (0x34, 0x05),
+    (0x35, 0x06),
+    (0x36, 0x07),
+    (0x37, 0x08),
+    (0x38, 0x09),
+    (0x39, 0x0A),
+    (0x30, 0x0B),
+    (0xBD, 0x0C),
+    (0xBB, 0x0D),
+    (0x08, 0x0E),
+    (0x09, 0x0F),
+    (0x51, 0x10),
+    (0x57, 0x11),
+    (0x45, 0x12),
+    (0x52, 0x13),
+    (0x54, 0x14),
+    (0x59, 0x15),
+    (0x55, 0x16),
+    (0x49, 0x17),
+    (0x4F, 0x18),
+    (0x50, 0x19),
+    (0xDB, 0x1A),
+    (0xDD, 0x1B),
+    (0x0D, 0x1C),
+    (0xA2, 0x1D),
+    (0x41, 0x1E),
+    (0x53, 0x1F),
+    (0x44, 0x20),
+    (0x46, 0x21),
+    (0x47, 0x22),
+    (0x48, 0x23),
+    (0x4A, 0x24),
+    (0x4B, 0x25),
+    (0x4C, 0x26),
+    (0xBA, 0x27),
+    (0xDE, 0x28),
+    (0xC0, 0x29),
+    (0xA0, 0x2A),
+    (0xDC, 0x2B),
+    (0x5A, 0x2C),
+    (0x58, 0x2D),
+    (0x43, 0x2E),
+    (0x56, 0x2F),
+    (0x42, 0x30),
+    (0x4E, 0x31),
+    (0x4D, 0x32),
+    (0xBC, 0x33),
+    (0xBE, 0x34),
+    (0xBF, 0x35),
+    (0xA1, 0x36),
+    (0xA4, 0x38),
+    (0x20, 0x39),
+    (0x14, 0x3A),
+    (0x70, 0x3B),
+    (0x71, 0x3C),
+    (0x72, 0x3D),
+    (0x73, 0x3E),
+    (0x74, 0x3F),
+    (0x75, 0x40),
+    (0x76, 0x41),
+    (0x77, 0x42),
+    (0x78, 0x43),
+    (0x79, 0x44),
+    (0x91, 0x46),
+    (0x90, 0x45),
+    (0x6A, 0x37),
+    (0x67, 0x47),
+    (0x68, 0x48),
+    (0x69, 0x49),
+    (0x6D, 0x4A),
+    (0x64, 0x4B),
+    (0x65, 0x4C),
+    (0x66, 0x4D),
+    (0x6B, 0x4E),
+    (0x61, 0x4F),
+    (0x62, 0x50),
+    (0x63, 0x51),
+    (0x60, 0x52),
+    (0x6E, 0x53),
+    (0x7A, 0x57),
+    (0x7B, 0x58),
+    (0x13, 0xE11D),
+    (VK_NUMPAD_ENTER, 0xE01C),
+    (0xA3, 0xE01D),
+    (0x2C, 0xE037),
+    (0x6F, 0xE035),
+    (0xA5, 0xE038),
+    (0x24, 0xE047),
+    (0x26, 0xE048),
+    (0x21, 0xE049),
+    (0x25, 0xE04B),
+    (0x27, 0xE04D),
+    (0x23, 0xE04F),
+    (0x28, 0xE050),
+    (0x22, 0xE051),
+    (0x2D, 0xE052),
+    (0x2E, 0xE053),
+    (0x5B, 0xE05B),
+    (0x5C, 0xE05C),
+    (0x5D, 0xE05D),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BuiltinLayout {
+    Ansi,
+    Iso,
+    Jis,
+    Sixty,
+}
+
+impl BuiltinLayout {
+    pub const ALL: [BuiltinLayout; 4] = [
+        BuiltinLayout::Ansi,
+        BuiltinLayout::Iso,
+        BuiltinLayout::Jis,
+        BuiltinLayout::Sixty,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            BuiltinLayout::Ansi => "ANSI",
+            BuiltinLayout::Iso => "ISO",
+            BuiltinLayout::Jis => "JIS",
+            BuiltinLayout::Sixty => "60%",
+        }
+    }
+
+    fn kle(self) -> &'static str {
+        match self {
+            BuiltinLayout::Ansi => include_str!("layouts/ansi.json"),
+            BuiltinLayout::Iso => include_str!("layouts/iso.json"),
+            BuiltinLayout::Jis => include_str!("layouts/jis.json"),
+            BuiltinLayout::Sixty => include_str!("layouts/sixty.json"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum LoadLayoutError {
+    Io(io::Error),
+    Parse(ParseKleError),
+}
+
+impl fmt::Display for LoadLayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadLayoutError::Io(err) => write!(f, "cannot read layout: {err}"),
+            LoadLayoutError::Parse(err) => write!(f, "invalid KLE layout: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for LoadLayoutError {}
+
+pub struct LayoutKey {
+    /// Position and size in key units.
+    pub rect: egui::Rect,
+    pub rect2: Option<egui::Rect>,
+    pub legends: Vec<String>,
+    pub scan_code: Option<u32>,
+}
+
+pub struct KeyboardLayout {
+    pub keys: Vec<LayoutKey>,
+    /// Extent of all keys in key units.
+    pub size: egui::Vec2,
+}
+
+impl KeyboardLayout {
+    pub fn builtin(layout: BuiltinLayout) -> Self {
+        // The built-in files are checked in and always parse
+        Self::from_kle(kle::parse(layout.kle()).unwrap_or_default())
+    }
+
+    pub fn load(path: &Path) -> Result<Self, LoadLayoutError> {
+        let text = fs::read_to_string(path).map_err(LoadLayoutError::Io)?;
+        let keys = kle::parse(&text).map_err(LoadLayoutError::Parse)?;
+        Ok(Self::from_kle(keys))
+    }
+
+    pub fn from_kle(keys: Vec<KleKey>) -> Self {
+        let numpad_x = main_block_right(&keys);
+        let mut modifier_counts = [0; 4];
+        let mut size = egui::Vec2::ZERO;
+
+        let keys = keys
+            .into_iter()
+            .map(|key| {
+                let rect =
+                    egui::Rect::from_min_size(egui::pos2(key.x, key.y), egui::vec2(key.w, key.h));
+                let rect2 = key.rect2.map(|[x, y, w, h]| {
+                    egui::Rect::from_min_size(egui::pos2(x, y), egui::vec2(w, h))
+                });
+                for r in std::iter::once(rect).chain(rect2) {
+                    size = size.max(r.max.to_vec2());
+                }
+                LayoutKey {
+                    rect,
+                    rect2,
+                    scan_code: legends_scan_code(
+                        &key.legends,
+                        key.x >= numpad_x,
+                        &mut modifier_counts,
+                    ),
+                    legends: key.legends,
+                }
+            })
+            .collect();
+
+        Self { keys, size }
+    }
+}
+
+/// Right edge of the keys that only the main block has (letters, Backspace and
+/// the punctuation keys that aren't on the numpad), or 0 when there are none,
+/// as in a numpad on its own.
+fn main_block_right(keys: &[KleKey]) -> f32 {
+    let is_main_block = |legend: &String| {
+        legend_vk(legend, false, &mut [0; 4]).is_some_and(
+            |vk| matches!(vk, 0x08 | 0x09 | 0x14 | 0x41..=0x5A | 0xBA | 0xC0 | 0xDB..=0xDE),
+        )
+    };
+    keys.iter()
+        .filter(|key| key.legends.iter().any(is_main_block))
+        .map(|key| key.x + key.w)
+        .fold(0.0, f32::max)
+}
+
+fn legends_scan_code(
+    legends: &[String],
+    is_numpad: bool,
+    modifier_counts: &mut [u32; 4],
+) -> Option<u32> {
+    if let Some(code) = legends
+        .iter()
+        .find_map(|legend| legend.strip_prefix("SC_"))
+        .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+    {
+        return Some(code);
+    }
+    legends
+        .iter()
+        .find_map(|legend| legend_vk(legend, is_numpad, modifier_counts))
+        .and_then(us_scan_code)
+}
+
+fn legend_vk(legend: &str, is_numpad: bool, modifier_counts: &mut [u32; 4]) -> Option<u32> {
+    let aliases = is_numpad
+        .then_some(NUMPAD_LEGENDS)
+        .into_iter()
+        .chain([LEGEND_ALIASES]);
+    for aliases in aliases {
+        if let Some(&(_, vk)) = aliases.iter().find(|&&(alias, _)| alias == legend) {
+            return Some(vk);
+        }
+    }
+
+    // Most key names are written without spaces ("CapsLock"), the numpad ones
+    // with a space ("Num 7")
+    let name: String = legend.chars().filter(|c| !c.is_whitespace()).collect();
+    let shortcut = Shortcut::parse(legend, &Default::default())
+        .or_else(|_| Shortcut::parse(&name, &Default::default()))
+        .ok()?;
+    match (shortcut.modifiers(), shortcut.keys()) {
+        (&[(modifier, side)], []) => {
+            let side = match side {
+                Side::Any => {
+                    let count = &mut modifier_counts[modifier as usize];
+                    *count += 1;
+                    if *count == 1 {
+                        Side::Left
+                    } else {
+                        Side::Right
+                    }
+                }
+                side => side,
+            };
+            Some(Modifier::to_vk(modifier, side))
+        }
+        ([], &[vk]) => Some(vk),
+        _ => None,
+    }
+}
+
+/// Scan code of `vk` on a US keyboard.
+fn us_scan_code(vk: u32) -> Option<u32> {
+    US_SCAN_CODES
+        .iter()
+        .find(|&&(code, _)| code == vk)
+        .map(|&(_, scan_code)| scan_code)
+}
+
+/// Draws `layout` scaled to the available width, filling the keys whose scan
+/// codes are in `pressed`.
+pub fn draw_keyboard(ui: &mut egui::Ui, layout: &KeyboardLayout, pressed: &HashSet<u32>) {
+    let visuals = ui.visuals().clone();
+    draw_keys(ui, layout, |key| {
+        if key.scan_code.is_some_and(|code| pressed.contains(&code)) {
+            (visuals.selection.bg_fill, visuals.selection.stroke.color)
+        } else {
+            (visuals.widgets.inactive.bg_fill, visuals.text_color())
+        }
+    });
+}
+
+/// Draws `layout` scaled to the available width, with the fill and legend
+/// colors returned by `colors` for each key.
+pub fn draw_keys(
+    ui: &mut egui::Ui,
+    layout: &KeyboardLayout,
+    colors: impl Fn(&LayoutKey) -> (egui::Color32, egui::Color32),
+) {
+    if layout.size.x <= 0.0 {
+        return;
+    }
+    let unit = (ui.available_width() / layout.size.x).min(MAX_KEY_SIZE);
+    let (_id, area) = ui.allocate_space(layout.size * unit);
+    let painter = ui.painter_at(area);
+
+    let gap = unit * 0.06;
+    let radius = unit * 0.12;
+    let font = egui::FontId::proportional(unit * 0.28);
+    let stroke = egui::Stroke::new(1.0, ui.visuals().widgets.inactive.bg_stroke.color);
+    let to_screen = |rect: egui::Rect| {
+        egui::Rect::from_min_size(area.min + rect.min.to_vec2() * unit, rect.size() * unit)
+            .shrink(gap)
+    };
+
+    for key in &layout.keys {
+        let (fill, text_color) = colors(key);
+        let rect = to_screen(key.rect);
+        let rect2 = key.rect2.map(to_screen);
+        for r in std::iter::once(rect).chain(rect2) {
+            painter.rect(r, radius, fill, stroke, egui::StrokeKind::Inside);
+        }
+        // Paint over the seam where a stepped key's rectangles overlap
+        if let Some(r2) = rect2 {
+            let overlap = rect.intersect(r2).shrink(1.0);
+            if overlap.is_positive() {
+                painter.rect_filled(overlap, 0.0, fill);
+            }
+        }
+
+        let legends: Vec<&str> = key
+            .legends
+            .iter()
+            .map(|legend| {
+                if legend.starts_with("SC_") {
+                    ""
+                } else {
+                    legend.as_str()
+                }
+            })
+            .collect();
+        let inner = rect.shrink(unit * 0.1);
+        match legends.as_slice() {
+            [single] => {
+                painter.text(
+                    inner.center(),
+                    egui::Align2::CENTER_CENTER,
+                    single,
+                    font.clone(),
+                    text_color,
+                );
+            }
+            _ => {
+                let anchors = [
+                    (inner.left_top(), egui::Align2::LEFT_TOP),
+                    (inner.left_bottom(), egui::Align2::LEFT_BOTTOM),
+                    (inner.right_top(), egui::Align2::RIGHT_TOP),
+                    (inner.right_bottom(), egui::Align2::RIGHT_BOTTOM),
+                ];
+                for (legend, (pos, align)) in legends.iter().zip(anchors) {
+                    painter.text(pos, align, legend, font.clone(), text_color);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scan_codes(text: &str) -> Vec<Option<u32>> {
+        let layout = KeyboardLayout::from_kle(kle::parse(text).unwrap());
+        layout.keys.iter().map(|key| key.scan_code).collect()
+    }
+
+    #[test]
+    fn builtin_layouts_map_every_key_once() {
+        for builtin in BuiltinLayout::ALL {
+            let keys = kle::parse(builtin.kle()).unwrap();
+            let layout = KeyboardLayout::from_kle(keys.clone());
+            let mut seen = HashSet::new();
+            for (key, kle_key) in layout.keys.iter().zip(&keys) {
+                let code = key.scan_code.unwrap_or_else(|| {
+                    panic!(
+                        "{}: {:?} has no scan code",
+                        builtin.label(),
+                        kle_key.legends
+                    )
+                });
+                assert!(
+                    seen.insert(code),
+                    "{}: {:?} repeats {code:#X}",
+                    builtin.label(),
+                    kle_key.legends
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn maps_legends_by_us_key_name() {
+        assert_eq!(
+            scan_codes(r#"["Esc", "!\n1", "Caps Lock", "PgUp", "↑", "Pause"]"#),
+            [
+                Some(0x01),
+                Some(0x02),
+                Some(0x3A),
+                Some(0xE049),
+                Some(0xE048),
+                Some(0xE11D)
+            ]
+        );
+        assert_eq!(scan_codes(r#"["Fancy"]"#), [None]);
+    }
+
+    #[test]
+    fn scan_code_legend_overrides_the_name() {
+        assert_eq!(
+            scan_codes(r#"["_\n\\\nSC_73", "Q\nSC_1E"]"#),
+            [Some(0x73), Some(0x1E)]
+        );
+    }
+
+    #[test]
+    fn assigns_unsided_modifiers_left_then_right() {
+        assert_eq!(
+            scan_codes(r#"["Shift", "Ctrl", "Alt", "Win", "Win", "Alt", "Ctrl", "Shift"]"#),
+            [
+                Some(0x2A),
+                Some(0x1D),
+                Some(0x38),
+                Some(0xE05B),
+                Some(0xE05C),
+                Some(0xE038),
+                Some(0xE01D),
+                Some(0x36)
+            ]
+        );
+        // A sided legend doesn't use up the left key
+        assert_eq!(
+            scan_codes(r#"["RShift", "Shift"]"#),
+            [Some(0x36), Some(0x2A)]
+        );
+    }
+
+    #[test]
+    fn maps_numpad_legends_right_of_the_main_block() {
+        let layout = r#"
+            ["+\n=", "Backspace", {x: 0.25}, "Home", {x: 0.25}, "Num Lock", "/", "*", "-"],
+            [{w: 1.5}, "Tab", "Q", {x: 0.25}, "End", {x: 0.25}, "7\nHome", "8\n↑", "9\nPgUp", "+"],
+            [{w: 2.5}, "Enter", {x: 0.25}, "↑", {x: 0.25}, "0\nIns", ".\nDel", "Enter"]
+        "#;
+        assert_eq!(
+            scan_codes(layout),
+            [
+                Some(0x0D),
+                Some(0x0E),
+                Some(0xE047),
+                Some(0x45),
+                Some(0xE035),
+                Some(0x37),
+                Some(0x4A),
+                Some(0x0F),
+                Some(0x10),
+                Some(0xE04F),
+                Some(0x47),
+                Some(0x48),
+                Some(0x49),
+                Some(0x4E),
+                Some(0x1C),
+                Some(0xE048),
+                Some(0x52),
+                Some(0x53),
+                Some(0xE01C)
+            ]
+        );
+    }
+
+    #[test]
+    fn maps_a_numpad_on_its_own() {
+        assert_eq!(
+            scan_codes(r#"["7", "8", "9"], ["0", "Enter"]"#),
+            [Some(0x47), Some(0x48), Some(0x49), Some(0x52), Some(0xE01C)]
+        );
+        // Numpad names need no position
+        assert_eq!(
+            scan_codes(r#"["Q", "Num 7", "Num Enter"]"#),
+            [Some(0x10), Some(0x47), Some(0xE01C)]
+        );
+    }
+
+    #[test]
+    fn sizes_layout_to_its_keys() {
+        let layout = KeyboardLayout::from_kle(
+            kle::parse(r#"["A", {w: 2}, "B"], [{x: 1, h: 2, x2: -0.25, w2: 1.5}, "C"]"#).unwrap(),
+        );
+        assert_eq!(layout.size, egui::vec2(3.0, 3.0));
+        assert_eq!(
+            layout.keys[2].rect2,
+            Some(egui::Rect::from_min_size(
+                egui::pos2(0.75, 1.0),
+                egui::vec2(1.5, 1.0)
+            ))
+        );
+    }
+}
@@ -0,0 +1,173 @@
+// Keycap rendering: each key of a combo is drawn as a rounded cap standing on
+// its own side wall, with a soft drop shadow. A held key sinks down onto the
+// wall and loses its shadow, so viewers can tell what is still pressed. The
+// label takes the theme's text color, the caps its corner radius, and the
+// border and shadow its outline.
+//
+// `keycap.svg` in the icons directory replaces the drawing. Its artwork is
+// drawn square at the cap height with the middle column stretched to fit the
+// label, and a `pressed` region fills while the key is held.
+
+use std::sync::Arc;
+
+use eframe::egui;
+
+use crate::{
+    icon::{DeviceIcon, IconError},
+    skin::Skin,
+    theme::{TextStyle, Theme},
+};
+
+// SVG regions of `keycap.svg`
+const KEYCAP_REGIONS: [&str; 1] = ["pressed"];
+
+// Proportions relative to the entry font size
+const LABEL_SCALE: f32 = 0.5;
+const CAP_HEIGHT_SCALE: f32 = 0.95;
+const DEPTH_SCALE: f32 = 0.1;
+const PADDING_SCALE: f32 = 0.25;
+
+/// Height of the cap face alone, for centering text next to keycaps.
+pub fn cap_height(font_size: f32) -> f32 {
+    font_size * CAP_HEIGHT_SCALE
+}
+
+/// Keycap artwork from `keycap.svg` in `icon::icons_dir`, or None to draw the
+/// caps in code.
+pub fn load_keycap_icon() -> Result<Option<DeviceIcon>, IconError> {
+    DeviceIcon::load_override("keycap.svg", &KEYCAP_REGIONS)
+}
+
+/// Where keycaps come from, in order of preference: skin images, SVG artwork,
+/// then `draw_keycap`.
+pub struct Keycaps<'a> {
+    pub skin: Option<&'a Skin>,
+    pub icon: Option<&'a mut DeviceIcon>,
+    pub theme: &'a Theme,
+}
+
+impl Keycaps<'_> {
+    /// Draws a keycap with its top-left corner at `pos` and returns the space
+    /// it took. `style` is the style of the entry text, faded to `alpha`.
+    pub fn draw(
+        &mut self,
+        ui: &egui::Ui,
+        pos: egui::Pos2,
+        label: &str,
+        style: &TextStyle,
+        is_pressed: bool,
+        alpha: u8,
+    ) -> egui::Vec2 {
+        if let Some(size) = self
+            .skin
+            .and_then(|skin| skin.draw_keycap(ui, pos, label, style, is_pressed, alpha))
+        {
+            return size;
+        }
+        let Some(icon) = &mut self.icon else {
+            return draw_keycap(ui, pos, label, style, self.theme, is_pressed, alpha);
+        };
+
+        // SVG artwork, as tall as the cap face of `draw_keycap`
+        let font_size = style.font.size;
+        let galley = label_galley(ui, label, style);
+        let height = cap_height(font_size);
+        let width = (galley.size().x + font_size * PADDING_SCALE * 2.0).max(height);
+        let rect = egui::Rect::from_min_size(pos, egui::vec2(width, height));
+        // Faded through the tint, so fading doesn't render the artwork again
+        icon.paint_stretched(
+            ui.painter(),
+            rect,
+            |region| is_pressed && region == "pressed",
+            self.theme.text_color,
+            ui.visuals().weak_text_color(),
+            egui::Color32::from_white_alpha(alpha),
+        );
+        ui.painter()
+            .galley(rect.center() - galley.size() / 2.0, galley, style.color);
+        rect.size()
+    }
+}
+
+// Label laid out in the entry's font at keycap size
+fn label_galley(ui: &egui::Ui, label: &str, style: &TextStyle) -> Arc<egui::Galley> {
+    let font = egui::FontId::new(style.font.size * LABEL_SCALE, style.font.family.clone());
+    ui.painter()
+        .layout_no_wrap(label.to_string(), font, style.color)
+}
+
+/// Draws a keycap with its top-left corner at `pos` and returns the space it
+/// took, including the side wall below the cap.
+pub fn draw_keycap(
+    ui: &egui::Ui,
+    pos: egui::Pos2,
+    label: &str,
+    style: &TextStyle,
+    theme: &Theme,
+    is_pressed: bool,
+    alpha: u8,
+) -> egui::Vec2 {
+    let visuals = ui.visuals();
+    let tint = egui::Color32::from_white_alpha(alpha);
+    let galley = label_galley(ui, label, style);
+
+    let font_size = style.font.size;
+    let cap_height = cap_height(font_size);
+    let depth = font_size * DEPTH_SCALE;
+    let radius = theme.corner_radius;
+    // Single characters get square caps
+    let cap_width = (galley.size().x + font_size * PADDING_SCALE * 2.0).max(cap_height);
+    let size = egui::vec2(cap_width, cap_height + depth);
+
+    let face = if is_pressed {
+        visuals.widgets.active.bg_fill
+    } else {
+        visuals.widgets.inactive.bg_fill
+    };
+    let wall = face.lerp_to_gamma(egui::Color32::BLACK, 0.4);
+    // The text outline, when shown, borders the caps
+    let border = match style.outline {
+        Some((color, width)) => egui::Stroke::new(width, color),
+        None => egui::Stroke::new(1.0, tint * visuals.widgets.inactive.bg_stroke.color),
+    };
+
+    let painter = ui.painter();
+    let wall_rect = egui::Rect::from_min_size(
+        pos + egui::vec2(0.0, depth),
+        egui::vec2(cap_width, cap_height),
+    );
+    if !is_pressed {
+        // Soft drop shadow, blurred over about the key's depth
+        let shadow = egui::epaint::Shadow {
+            offset: [0, (depth * 0.5).round() as i8],
+            blur: (depth * 1.5).round() as u8,
+            spread: 1,
+            color: theme.outline_color.gamma_multiply(alpha as f32 / 255.0),
+        };
+        painter.add(shadow.as_shape(wall_rect, radius));
+    }
+    painter.rect(
+        wall_rect,
+        radius,
+        tint * wall,
+        border,
+        egui::StrokeKind::Inside,
+    );
+
+    // The cap travels most of the way down onto its wall while pressed
+    let travel = if is_pressed { depth * 0.8 } else { 0.0 };
+    let cap_rect = egui::Rect::from_min_size(
+        pos + egui::vec2(0.0, travel),
+        egui::vec2(cap_width, cap_height),
+    );
+    painter.rect(
+        cap_rect,
+        radius,
+        tint * face,
+        border,
+        egui::StrokeKind::Inside,
+    );
+    painter.galley(cap_rect.center() - galley.size() / 2.0, galley, style.color);
+
+    size
+}
@@ -0,0 +1,385 @@
+// Parser for keyboard-layout-editor.com (KLE) layouts.
+//
+// KLE stores a keyboard as rows of keys. Each row is an array of legend
+// strings, one per key, with property objects in between that change the
+// following keys: `x`/`y` shift the position, `w`/`h` size the next key and
+// `x2`/`y2`/`w2`/`h2` give it a second rectangle (stepped or ISO Enter keys).
+// Positions and sizes are in key units. Legends are separated by newlines.
+//
+// Both the downloaded .json and the "raw data" text from the site's editor are
+// accepted. The latter is relaxed JSON: property names are unquoted and the
+// outer brackets are missing. Rotated clusters (`r`, `rx`, `ry`) are laid out
+// unrotated, decals (`d`) are skipped and ghosted keys (`g`) are kept as
+// ordinary keys.
+
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct KleKey {
+    pub x: f32,
+    pub y: f32,
+    pub w: f32,
+    pub h: f32,
+    /// Second rectangle of a stepped key, relative to the layout origin.
+    pub rect2: Option<[f32; 4]>,
+    pub legends: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseKleError {
+    /// Malformed JSON at this byte offset.
+    Syntax(usize),
+    /// Valid JSON that isn't a list of key rows.
+    NotRows,
+}
+
+impl fmt::Display for ParseKleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseKleError::Syntax(offset) => write!(f, "syntax error at byte {offset}"),
+            ParseKleError::NotRows => write!(f, "expected rows of keys"),
+        }
+    }
+}
+
+impl std::error::Error for ParseKleError {}
+
+enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+pub fn parse(text: &str) -> Result<Vec<KleKey>, ParseKleError> {
+    let mut parser = Parser {
+        text: text.as_bytes(),
+        pos: 0,
+    };
+    // Raw data is a bare comma-separated list of rows
+    let mut values = vec![parser.value()?];
+    while parser.eat(b',') && parser.peek().is_some() {
+        values.push(parser.value()?);
+    }
+    parser.skip_whitespace();
+    if parser.pos < parser.text.len() {
+        return Err(ParseKleError::Syntax(parser.pos));
+    }
+
+    let rows = match values.as_slice() {
+        [Value::Array(items)] if items.iter().all(|item| !matches!(item, Value::String(_))) => {
+            items
+        }
+        _ => &values,
+    };
+    layout_rows(rows)
+}
+
+fn layout_rows(rows: &[Value]) -> Result<Vec<KleKey>, ParseKleError> {
+    let mut keys = Vec::new();
+    let mut y = 0.0;
+
+    for (i, row) in rows.iter().enumerate() {
+        let items = match row {
+            Value::Array(items) => items,
+            // Keyboard metadata (name, author, background) comes first
+            Value::Object(_) if i == 0 => continue,
+            _ => return Err(ParseKleError::NotRows),
+        };
+
+        let mut x = 0.0;
+        let mut size = (1.0, 1.0);
+        let mut second: Option<[f32; 4]> = None;
+        let mut is_decal = false;
+
+        for item in items {
+            match item {
+                Value::Object(props) => {
+                    for (name, value) in props {
+                        let Value::Number(n) = value else {
+                            if name == "d" {
+                                is_decal = matches!(value, Value::Bool(true));
+                            }
+                            continue;
+                        };
+                        let n = *n as f32;
+                        match name.as_str() {
+                            "x" => x += n,
+                            "y" => y += n,
+                            "w" => size.0 = n,
+                            "h" => size.1 = n,
+                            "x2" => second.get_or_insert([0.0, 0.0, 1.0, 1.0])[0] = n,
+                            "y2" => second.get_or_insert([0.0, 0.0, 1.0, 1.0])[1] = n,
+                            "w2" => second.get_or_insert([0.0, 0.0, 1.0, 1.0])[2] = n,
+                            "h2" => second.get_or_insert([0.0, 0.0, 1.0, 1.0])[3] = n,
+                            _ => {}
+                        }
+                    }
+                }
+                Value::String(legend) => {
+                    if !is_decal {
+                        keys.push(KleKey {
+                            x,
+                            y,
+                            w: size.0,
+                            h: size.1,
+                            rect2: second.map(|[x2, y2, w2, h2]| [x + x2, y + y2, w2, h2]),
+                            legends: legend.split('\n').map(str::to_string).collect(),
+                        });
+                    }
+                    x += size.0;
+                    size = (1.0, 1.0);
+                    second = None;
+                    is_decal = false;
+                }
+                _ => return Err(ParseKleError::NotRows),
+            }
+        }
+        y += 1.0;
+    }
+    Ok(keys)
+}
+
+struct Parser<'a> {
+    text: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self.text.get(self.pos).is_some_and(u8::is_ascii_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_whitespace();
+        self.text.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        if self.peek() == Some(byte) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn error(&self) -> ParseKleError {
+        ParseKleError::Syntax(self.pos)
+    }
+
+    fn value(&mut self) -> Result<Value, ParseKleError> {
+        match self.peek().ok_or_else(|| self.error())? {
+            b'[' => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                // Trailing commas are common in hand-edited raw data
+                while !self.eat(b']') {
+                    items.push(self.value()?);
+                    if !self.eat(b',') && self.peek() != Some(b']') {
+                        return Err(self.error());
+                    }
+                }
+                Ok(Value::Array(items))
+            }
+            b'{' => {
+                self.pos += 1;
+                let mut props = Vec::new();
+                while !self.eat(b'}') {
+                    let name = match self.peek() {
+                        Some(b'"') => self.string()?,
+                        _ => self.word(),
+                    };
+                    if name.is_empty() || !self.eat(b':') {
+                        return Err(self.error());
+                    }
+                    props.push((name, self.value()?));
+                    if !self.eat(b',') && self.peek() != Some(b'}') {
+                        return Err(self.error());
+                    }
+                }
+                Ok(Value::Object(props))
+            }
+            b'"' => self.string().map(Value::String),
+            _ => {
+                let start = self.pos;
+                match self.word().as_str() {
+                    "true" => Ok(Value::Bool(true)),
+                    "false" => Ok(Value::Bool(false)),
+                    "null" => Ok(Value::Null),
+                    word => word
+                        .parse()
+                        .map(Value::Number)
+                        .map_err(|_| ParseKleError::Syntax(start)),
+                }
+            }
+        }
+    }
+
+    // Unquoted property name, number or literal
+    fn word(&mut self) -> String {
+        self.skip_whitespace();
+        let start = self.pos;
+        while self
+            .text
+            .get(self.pos)
+            .is_some_and(|&b| b.is_ascii_alphanumeric() || matches!(b, b'_' | b'-' | b'+' | b'.'))
+        {
+            self.pos += 1;
+        }
+        String::from_utf8_lossy(&self.text[start..self.pos]).into_owned()
+    }
+
+    fn string(&mut self) -> Result<String, ParseKleError> {
+        // Skip the opening quote
+        self.pos += 1;
+        let mut bytes = Vec::new();
+        loop {
+            let byte = *self.text.get(self.pos).ok_or_else(|| self.error())?;
+            self.pos += 1;
+            match byte {
+                b'"' => break,
+                b'\\' => {
+                    let escaped = *self.text.get(self.pos).ok_or_else(|| self.error())?;
+                    self.pos += 1;
+                    match escaped {
+                        b'n' => bytes.push(b'\n'),
+                        b't' => bytes.push(b'\t'),
+                        b'r' => bytes.push(b'\r'),
+                        b'b' | b'f' => {}
+                        b'u' => {
+                            let hex = self
+                                .text
+                                .get(self.pos..self.pos + 4)
+                                .ok_or_else(|| self.error())?;
+                            let code = std::str::from_utf8(hex)
+                                .ok()
+                                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                                .ok_or_else(|| self.error())?;
+                            self.pos += 4;
+                            let c = char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER);
+                            bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                        }
+                        other => bytes.push(other),
+                    }
+                }
+                other => bytes.push(other),
+            }
+        }
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(x: f32, y: f32, w: f32, h: f32, legends: &[&str]) -> KleKey {
+        KleKey {
+            x,
+            y,
+            w,
+            h,
+            rect2: None,
+            legends: legends.iter().map(|legend| legend.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn parses_json_and_raw_data_alike() {
+        let json = r#"[{"name": "Test"}, ["Esc", {"x": 1}, "F1"], [{"w": 1.5}, "Tab", "!\n1"]]"#;
+        let raw = "{name: \"Test\"},\n[\"Esc\", {x: 1}, \"F1\"],\n[{w: 1.5}, \"Tab\", \"!\\n1\",],";
+        let expected = [
+            key(0.0, 0.0, 1.0, 1.0, &["Esc"]),
+            key(2.0, 0.0, 1.0, 1.0, &["F1"]),
+            key(0.0, 1.0, 1.5, 1.0, &["Tab"]),
+            key(1.5, 1.0, 1.0, 1.0, &["!", "1"]),
+        ];
+        assert_eq!(parse(json).unwrap(), expected);
+        assert_eq!(parse(raw).unwrap(), expected);
+    }
+
+    #[test]
+    fn carries_positions_but_not_sizes() {
+        let keys = parse(
+            r#"[{"x": 0.5, "w": 2, "h": 2}, "A", "B", {"x": 0.25}, "C"],
+               [{"y": 0.5}, "D"],
+               ["E"]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            keys,
+            [
+                key(0.5, 0.0, 2.0, 2.0, &["A"]),
+                key(2.5, 0.0, 1.0, 1.0, &["B"]),
+                key(3.75, 0.0, 1.0, 1.0, &["C"]),
+                key(0.0, 1.5, 1.0, 1.0, &["D"]),
+                key(0.0, 2.5, 1.0, 1.0, &["E"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn places_second_rectangle_relative_to_the_key() {
+        // ISO Enter
+        let keys = parse(
+            r#"[{"x": 12.75, "w": 1.25, "h": 2, "w2": 1.5, "h2": 1, "x2": -0.25}, "Enter", "A"]"#,
+        )
+        .unwrap();
+        assert_eq!(keys[0].rect2, Some([12.5, 0.0, 1.5, 1.0]));
+        // The second rectangle is only for the key it was given with
+        assert_eq!(keys[1].rect2, None);
+        assert_eq!(keys[1].x, 14.0);
+
+        // Unset fields default to the first rectangle's unit key
+        let keys = parse(r#"[{"y2": 1}, "A"]"#).unwrap();
+        assert_eq!(keys[0].rect2, Some([0.0, 1.0, 1.0, 1.0]));
+    }
+
+    #[test]
+    fn skips_decals_but_keeps_ghost_keys() {
+        let keys = parse(r#"[{"d": true, "w": 2}, "Logo", "A", {"g": true}, "B", "C"]"#).unwrap();
+        assert_eq!(
+            keys,
+            [
+                key(2.0, 0.0, 1.0, 1.0, &["A"]),
+                key(3.0, 0.0, 1.0, 1.0, &["B"]),
+                key(4.0, 0.0, 1.0, 1.0, &["C"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn decodes_string_escapes() {
+        let keys = parse(r#"[["\"\n'", "↑", "\\"]]"#).unwrap();
+        let legends: Vec<_> = keys.iter().map(|key| key.legends.clone()).collect();
+        assert_eq!(legends, [vec!["\"", "'"], vec!["↑"], vec!["\\"]]);
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        let cases = [
+            ("", ParseKleError::Syntax(0)),
+            ("[", ParseKleError::Syntax(1)),
+            (r#"[["A""#, ParseKleError::Syntax(5)),
+            (r#"[["A" "B"]]"#, ParseKleError::Syntax(6)),
+            (r#"[[{x: }, "A"]]"#, ParseKleError::Syntax(6)),
+            (r#"[["\u12"]]"#, ParseKleError::Syntax(5)),
+            (r#"["A"] junk"#, ParseKleError::Syntax(6)),
+            ("[1]", ParseKleError::NotRows),
+            (r#"[["A", 1]]"#, ParseKleError::NotRows),
+            (r#"[["A"], {"name": "late"}]"#, ParseKleError::NotRows),
+        ];
+        for (text, error) in cases {
+            assert_eq!(parse(text), Err(error), "{text}");
+        }
+        // Arbitrary bytes never panic
+        for text in ["\u{0}\u{ff}", "{{{{", "]]]", "[\"\\", "[{\"x\": 1e999}]"] {
+            let _ = parse(text);
+        }
+    }
+}
@@ -0,0 +1,540 @@
+// UI and key-name translations.
+//
+// Messages are keyed by their English text, so a missing translation simply
+// falls back to English. Key names are translated from the labels produced by
+// `key_names`, which keeps the database itself language-neutral.
+
+use eframe::egui;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Locale {
+    English,
+    Korean,
+    German,
+    French,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 4] = [
+        Locale::English,
+        Locale::Korean,
+        Locale::German,
+        Locale::French,
+    ];
+
+    /// Name of the language in that language, for the settings selector.
+    pub fn native_name(self) -> &'static str {
+        match self {
+            Locale::English => "English",
+            Locale::Korean => "한국어",
+            Locale::German => "Deutsch",
+            Locale::French => "Français",
+        }
+    }
+
+    /// Matches a BCP 47 tag ("ko-KR") or POSIX locale ("de_DE.UTF-8") by its
+    /// language subtag.
+    pub fn from_tag(tag: &str) -> Option<Locale> {
+        let language = tag
+            .split(['-', '_', '.'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match language.as_str() {
+            "en" => Some(Locale::English),
+            "ko" => Some(Locale::Korean),
+            "de" => Some(Locale::German),
+            "fr" => Some(Locale::French),
+            _ => None,
+        }
+    }
+
+    pub fn system() -> Locale {
+        crate::platform::system_locale_name()
+            .and_then(|tag| Locale::from_tag(&tag))
+            .unwrap_or(Locale::English)
+    }
+}
+
+/// Translates a UI message, falling back to the English text.
+pub fn tr(locale: Locale, text: &str) -> &str {
+    let translated = match locale {
+        Locale::English => None,
+        Locale::Korean => korean_ui(text),
+        Locale::German => german_ui(text),
+        Locale::French => french_ui(text),
+    };
+    translated.unwrap_or(text)
+}
+
+/// Translates a key label from `key_names`, falling back to the English name.
+pub fn key_name(locale: Locale, name: &str) -> &str {
+    let translated = match locale {
+        Locale::English => None,
+        Locale::Korean => korean_key(name),
+        Locale::German => german_key(name),
+        Locale::French => french_key(name),
+    };
+    translated.unwrap_or(name)
+}
+
+fn korean_ui(text: &str) -> Option<&'static str> {
+    let translated = match text {
+        "Outline Text" => "외곽선",
+        "Show Mouse" => "마우스 표시",
+        "Show Gamepad" => "게임패드 표시",
+        "Overlay" => "오버레이",
+        "Settings" => "설정",
+        "Keyboim Settings" => "Keyboim 설정",
+        "Language" => "언어",
+        "System" => "시스템",
+        "Modifiers" => "보조 키",
+        "Distinguish left/right" => "왼쪽/오른쪽 구분",
+        "Modifier order" => "보조 키 순서",
+        "Sequence prefixes" => "연속 입력 접두 키",
+        "One shortcut per line, e.g. Ctrl+K or Ctrl+X" => "한 줄에 하나씩, 예: Ctrl+K 또는 Ctrl+X",
+        "Sequence timeout" => "연속 입력 제한 시간",
+        "Hold duration" => "누름 시간",
+        "Show tap/hold" => "탭/홀드 표시",
+        "Hold threshold" => "홀드 기준 시간",
+        "tap" => "탭",
+        "hold" => "홀드",
+        "Mouse" => "마우스",
+        "Include in combos" => "조합에 포함",
+        "Privacy" => "개인정보",
+        "Shortcuts only (hide typing)" => "단축키만 표시 (입력 숨김)",
+        "Display" => "표시 방식",
+        "Combo" => "조합",
+        "Ticker" => "입력 흐름",
+        "Double {button}" => "더블 {button}",
+        "Triple {button}" => "트리플 {button}",
+        "{drag} start" => "{drag} 시작",
+        "{drag} end, {distance} px" => "{drag} 끝, {distance} px",
+        "Long {button}" => "길게 {button}",
+        "Gestures" => "제스처",
+        "Show mouse gestures" => "마우스 제스처 표시",
+        "Double-click time" => "더블 클릭 간격",
+        "Long-press time" => "길게 누르기 시간",
+        "History" => "기록",
+        "History order" => "기록 순서",
+        "Newest on top" => "최신 항목 위",
+        "Newest at bottom" => "최신 항목 아래",
+        "Lifetime" => "표시 유지",
+        "Timed" => "시간 제한",
+        "Show while held" => "누르는 동안 표시",
+        "Always show last" => "마지막 항목 계속 표시",
+        "Display time" => "표시 시간",
+        "Fade time" => "사라지는 시간",
+        "Key style" => "키 표시 방식",
+        "Text" => "텍스트",
+        "Keycaps" => "키캡",
+        "Skin" => "스킨",
+        "Built-in" => "기본",
+        "Keyboard" => "키보드",
+        "Show keyboard" => "키보드 표시",
+        "Keyboard layout" => "키보드 배열",
+        "KLE file" => "KLE 파일",
+        "Path to a keyboard-layout-editor.com JSON file" => {
+            "keyboard-layout-editor.com JSON 파일 경로"
+        }
+        "Heatmap" => "히트맵",
+        "Record key usage" => "키 사용 기록",
+        "Show heatmap" => "히트맵 표시",
+        "Color the keyboard by how often each key was pressed" => {
+            "키를 누른 횟수에 따라 키보드에 색을 칠합니다"
+        }
+        "Color scale" => "색상 척도",
+        "Heat" => "열",
+        "Viridis" => "Viridis",
+        "Cool to warm" => "차가움에서 따뜻함",
+        "Grayscale" => "회색조",
+        "Heatmap data" => "히트맵 데이터",
+        "This session" => "이번 세션",
+        "All sessions" => "모든 세션",
+        "Save session" => "세션 저장",
+        "Export PNG" => "PNG 내보내기",
+        "Reset" => "초기화",
+        "Saved to {path}" => "{path}에 저장했습니다",
+        "No keyboard layout loaded" => "불러온 키보드 배열이 없습니다",
+        "Click ripples" => "클릭 물결",
+        "Show click ripples" => "클릭 물결 표시",
+        "Cursor halo" => "커서 후광",
+        "Show cursor halo" => "커서 후광 표시",
+        "Motion trail" => "이동 궤적",
+        "Gesture" => "제스처",
+        "None" => "없음",
+        "Fade" => "페이드",
+        "Pop" => "팝",
+        "Slide" => "슬라이드",
+        "Left" => "왼쪽",
+        "Right" => "오른쪽",
+        "Top" => "위",
+        "Bottom" => "아래",
+        "Linear" => "선형",
+        "Ease in" => "가속",
+        "Ease out" => "감속",
+        "Ease in and out" => "가속 후 감속",
+        "Overshoot" => "튕김",
+        "Animation" => "애니메이션",
+        "Appear" => "나타남",
+        "Disappear" => "사라짐",
+        "Slide edge" => "슬라이드 방향",
+        "Easing" => "이징",
+        "Animation time" => "애니메이션 시간",
+        "Bounce on repeat" => "반복 시 튕기기",
+        "Theme" => "테마",
+        "Light" => "밝게",
+        "Dark" => "어둡게",
+        "High contrast" => "고대비",
+        _ => return None,
+    };
+    Some(translated)
+}
+
+fn german_ui(text: &str) -> Option<&'static str> {
+    let translated = match text {
+        "Outline Text" => "Text umranden",
+        "Show Mouse" => "Maus anzeigen",
+        "Show Gamepad" => "Gamepad anzeigen",
+        "Overlay" => "Overlay",
+        "Settings" => "Einstellungen",
+        "Keyboim Settings" => "Keyboim-Einstellungen",
+        "Language" => "Sprache",
+        "System" => "System",
+        "Modifiers" => "Sondertasten",
+        "Distinguish left/right" => "Links/rechts unterscheiden",
+        "Modifier order" => "Reihenfolge",
+        "Sequence prefixes" => "Sequenz-Präfixe",
+        "One shortcut per line, e.g. Ctrl+K or Ctrl+X" => {
+            "Ein Kürzel pro Zeile, z. B. Strg+K oder Strg+X"
+        }
+        "Sequence timeout" => "Sequenz-Zeitlimit",
+        "Hold duration" => "Haltedauer",
+        "Show tap/hold" => "Tippen/Halten anzeigen",
+        "Hold threshold" => "Halte-Schwelle",
+        "tap" => "tippen",
+        "hold" => "halten",
+        "Mouse" => "Maus",
+        "Include in combos" => "In Kombinationen einbeziehen",
+        "Privacy" => "Privatsphäre",
+        "Shortcuts only (hide typing)" => "Nur Tastenkürzel (Tippen ausblenden)",
+        "Display" => "Anzeige",
+        "Combo" => "Kombination",
+        "Ticker" => "Laufschrift",
+        "Double {button}" => "Doppel-{button}",
+        "Triple {button}" => "Dreifach-{button}",
+        "{drag} start" => "{drag} (Beginn)",
+        "{drag} end, {distance} px" => "{drag} (Ende), {distance} px",
+        "Long {button}" => "Langer {button}",
+        "Gestures" => "Gesten",
+        "Show mouse gestures" => "Mausgesten anzeigen",
+        "Double-click time" => "Doppelklickzeit",
+        "Long-press time" => "Dauer für langes Drücken",
+        "History" => "Verlauf",
+        "History order" => "Verlaufsreihenfolge",
+        "Newest on top" => "Neueste oben",
+        "Newest at bottom" => "Neueste unten",
+        "Lifetime" => "Anzeigedauer",
+        "Timed" => "Zeitgesteuert",
+        "Show while held" => "Anzeigen solange gedrückt",
+        "Always show last" => "Letzte immer anzeigen",
+        "Display time" => "Anzeigezeit",
+        "Fade time" => "Ausblendzeit",
+        "Key style" => "Tastenstil",
+        "Text" => "Text",
+        "Keycaps" => "Tastenkappen",
+        "Skin" => "Skin",
+        "Built-in" => "Integriert",
+        "Keyboard" => "Tastatur",
+        "Show keyboard" => "Tastatur anzeigen",
+        "Keyboard layout" => "Tastaturlayout",
+        "KLE file" => "KLE-Datei",
+        "Path to a keyboard-layout-editor.com JSON file" => {
+            "Pfad zu einer JSON-Datei von keyboard-layout-editor.com"
+        }
+        "Heatmap" => "Heatmap",
+        "Record key usage" => "Tastennutzung aufzeichnen",
+        "Show heatmap" => "Heatmap anzeigen",
+        "Color the keyboard by how often each key was pressed" => {
+            "Färbt die Tastatur danach, wie oft jede Taste gedrückt wurde"
+        }
+        "Color scale" => "Farbskala",
+        "Heat" => "Hitze",
+        "Viridis" => "Viridis",
+        "Cool to warm" => "Kalt bis warm",
+        "Grayscale" => "Graustufen",
+        "Heatmap data" => "Heatmap-Daten",
+        "This session" => "Diese Sitzung",
+        "All sessions" => "Alle Sitzungen",
+        "Save session" => "Sitzung speichern",
+        "Export PNG" => "Als PNG exportieren",
+        "Reset" => "Zurücksetzen",
+        "Saved to {path}" => "Gespeichert unter {path}",
+        "No keyboard layout loaded" => "Kein Tastaturlayout geladen",
+        "Click ripples" => "Klickwellen",
+        "Show click ripples" => "Klickwellen anzeigen",
+        "Cursor halo" => "Zeigerhalo",
+        "Show cursor halo" => "Zeigerhalo anzeigen",
+        "Motion trail" => "Bewegungsspur",
+        "Gesture" => "Geste",
+        "None" => "Keine",
+        "Fade" => "Blenden",
+        "Pop" => "Aufploppen",
+        "Slide" => "Schieben",
+        "Left" => "Links",
+        "Right" => "Rechts",
+        "Top" => "Oben",
+        "Bottom" => "Unten",
+        "Linear" => "Linear",
+        "Ease in" => "Beschleunigen",
+        "Ease out" => "Abbremsen",
+        "Ease in and out" => "Beschleunigen und abbremsen",
+        "Overshoot" => "Überschwingen",
+        "Animation" => "Animation",
+        "Appear" => "Erscheinen",
+        "Disappear" => "Verschwinden",
+        "Slide edge" => "Schieberichtung",
+        "Easing" => "Verlauf",
+        "Animation time" => "Animationsdauer",
+        "Bounce on repeat" => "Bei Wiederholung hüpfen",
+        "Theme" => "Design",
+        "Light" => "Hell",
+        "Dark" => "Dunkel",
+        "High contrast" => "Hoher Kontrast",
+        _ => return None,
+    };
+    Some(translated)
+}
+
+fn french_ui(text: &str) -> Option<&'static str> {
+    let translated = match text {
+        "Outline Text" => "Contour du texte",
+        "Show Mouse" => "Afficher la souris",
+        "Show Gamepad" => "Afficher la manette",
+        "Overlay" => "Superposition",
+        "Settings" => "Paramètres",
+        "Keyboim Settings" => "Paramètres de Keyboim",
+        "Language" => "Langue",
+        "System" => "Système",
+        "Modifiers" => "Modificateurs",
+        "Distinguish left/right" => "Distinguer gauche/droite",
+        "Modifier order" => "Ordre des modificateurs",
+        "Sequence prefixes" => "Préfixes de séquence",
+        "One shortcut per line, e.g. Ctrl+K or Ctrl+X" => {
+            "Un raccourci par ligne, p. ex. Ctrl+K ou Ctrl+X"
+        }
+        "Sequence timeout" => "Délai de séquence",
+        "Hold duration" => "Durée d'appui",
+        "Show tap/hold" => "Afficher appui/maintien",
+        "Hold threshold" => "Seuil de maintien",
+        "tap" => "appui",
+        "hold" => "maintien",
+        "Mouse" => "Souris",
+        "Include in combos" => "Inclure dans les combinaisons",
+        "Privacy" => "Confidentialité",
+        "Shortcuts only (hide typing)" => "Raccourcis uniquement (masquer la saisie)",
+        "Display" => "Affichage",
+        "Combo" => "Combinaison",
+        "Ticker" => "Défilement",
+        "Double {button}" => "{button} double",
+        "Triple {button}" => "{button} triple",
+        "{drag} start" => "{drag} (début)",
+        "{drag} end, {distance} px" => "{drag} (fin), {distance} px",
+        "Long {button}" => "{button} long",
+        "Gestures" => "Gestes",
+        "Show mouse gestures" => "Afficher les gestes de la souris",
+        "Double-click time" => "Délai du double-clic",
+        "Long-press time" => "Durée de l'appui long",
+        "History" => "Historique",
+        "History order" => "Ordre de l'historique",
+        "Newest on top" => "Plus récent en haut",
+        "Newest at bottom" => "Plus récent en bas",
+        "Lifetime" => "Durée d'affichage",
+        "Timed" => "Minutée",
+        "Show while held" => "Afficher tant que maintenu",
+        "Always show last" => "Toujours afficher la dernière",
+        "Display time" => "Temps d'affichage",
+        "Fade time" => "Temps de fondu",
+        "Key style" => "Style des touches",
+        "Text" => "Texte",
+        "Keycaps" => "Touches",
+        "Skin" => "Thème graphique",
+        "Built-in" => "Intégré",
+        "Keyboard" => "Clavier",
+        "Show keyboard" => "Afficher le clavier",
+        "Keyboard layout" => "Disposition du clavier",
+        "KLE file" => "Fichier KLE",
+        "Path to a keyboard-layout-editor.com JSON file" => {
+            "Chemin d'un fichier JSON de keyboard-layout-editor.com"
+        }
+        "Heatmap" => "Carte de chaleur",
+        "Record key usage" => "Enregistrer l'utilisation des touches",
+        "Show heatmap" => "Afficher la carte de chaleur",
+        "Color the keyboard by how often each key was pressed" => {
+            "Colore le clavier selon le nombre d'appuis sur chaque touche"
+        }
+        "Color scale" => "Échelle de couleurs",
+        "Heat" => "Chaleur",
+        "Viridis" => "Viridis",
+        "Cool to warm" => "Du froid au chaud",
+        "Grayscale" => "Niveaux de gris",
+        "Heatmap data" => "Données de la carte",
+        "This session" => "Cette session",
+        "All sessions" => "Toutes les sessions",
+        "Save session" => "Enregistrer la session",
+        "Export PNG" => "Exporter en PNG",
+        "Reset" => "Réinitialiser",
+        "Saved to {path}" => "Enregistré dans {path}",
+        "No keyboard layout loaded" => "Aucune disposition de clavier chargée",
+        "Click ripples" => "Ondes de clic",
+        "Show click ripples" => "Afficher les ondes de clic",
+        "Cursor halo" => "Halo du curseur",
+        "Show cursor halo" => "Afficher le halo du curseur",
+        "Motion trail" => "Traînée de mouvement",
+        "Gesture" => "Geste",
+        "None" => "Aucun",
+        "Fade" => "Fondu",
+        "Pop" => "Surgissement",
+        "Slide" => "Glissement",
+        "Left" => "Gauche",
+        "Right" => "Droite",
+        "Top" => "Haut",
+        "Bottom" => "Bas",
+        "Linear" => "Linéaire",
+        "Ease in" => "Accélération",
+        "Ease out" => "Décélération",
+        "Ease in and out" => "Accélération et décélération",
+        "Overshoot" => "Dépassement",
+        "Animation" => "Animation",
+        "Appear" => "Apparition",
+        "Disappear" => "Disparition",
+        "Slide edge" => "Bord de glissement",
+        "Easing" => "Courbe",
+        "Animation time" => "Durée de l'animation",
+        "Bounce on repeat" => "Rebond à la répétition",
+        "Theme" => "Thème",
+        "Light" => "Clair",
+        "Dark" => "Sombre",
+        "High contrast" => "Contraste élevé",
+        _ => return None,
+    };
+    Some(translated)
+}
+
+fn korean_key(name: &str) -> Option<&'static str> {
+    // Korean keyboards print Latin legends except for the IME keys; mouse
+    // actions read better in Korean
+    let translated = match name {
+        "Hangul" => "한/영",
+        "Hanja" => "한자",
+        "Click" => "클릭",
+        "Right Click" => "우클릭",
+        "Middle Click" => "휠 클릭",
+        "Drag" => "드래그",
+        "Scroll ⬆" => "스크롤 ⬆",
+        "Scroll ⬇" => "스크롤 ⬇",
+        "Scroll ⬅" => "스크롤 ⬅",
+        "Scroll ➡" => "스크롤 ➡",
+        _ => return None,
+    };
+    Some(translated)
+}
+
+fn german_key(name: &str) -> Option<&'static str> {
+    let translated = match name {
+        "Ctrl" => "Strg",
+        "LCtrl" => "LStrg",
+        "RCtrl" => "RStrg",
+        "Shift" => "Umschalt",
+        "LShift" => "LUmschalt",
+        "RShift" => "RUmschalt",
+        "Delete" => "Entf",
+        "Insert" => "Einfg",
+        "Home" => "Pos1",
+        "End" => "Ende",
+        "PageUp" => "Bild ⬆",
+        "PageDown" => "Bild ⬇",
+        "Enter" => "Eingabe",
+        "Num Enter" => "Num Eingabe",
+        "Space" => "Leertaste",
+        "Backspace" => "Rücktaste",
+        "CapsLock" => "Feststell",
+        "PrintScreen" => "Druck",
+        "ScrollLock" => "Rollen",
+        "NumLock" => "Num",
+        "Apps" => "Menü",
+        "Click" => "Klick",
+        "Right Click" => "Rechtsklick",
+        "Middle Click" => "Mittelklick",
+        "Drag" => "Ziehen",
+        "Scroll ⬆" => "Scrollen ⬆",
+        "Scroll ⬇" => "Scrollen ⬇",
+        "Scroll ⬅" => "Scrollen ⬅",
+        "Scroll ➡" => "Scrollen ➡",
+        _ => return None,
+    };
+    Some(translated)
+}
+
+fn french_key(name: &str) -> Option<&'static str> {
+    let translated = match name {
+        "Shift" => "Maj",
+        "LShift" => "LMaj",
+        "RShift" => "RMaj",
+        "Delete" => "Suppr",
+        "Insert" => "Inser",
+        "Home" => "Origine",
+        "End" => "Fin",
+        "PageUp" => "Page ⬆",
+        "PageDown" => "Page ⬇",
+        "Enter" => "Entrée",
+        "Num Enter" => "Num Entrée",
+        "Space" => "Espace",
+        "Backspace" => "Retour arrière",
+        "Esc" => "Échap",
+        "CapsLock" => "Verr Maj",
+        "PrintScreen" => "Impr écran",
+        "ScrollLock" => "Arrêt défil",
+        "NumLock" => "Verr Num",
+        "Apps" => "Menu",
+        "Click" => "Clic",
+        "Right Click" => "Clic droit",
+        "Middle Click" => "Clic milieu",
+        "Drag" => "Glisser",
+        "Scroll ⬆" => "Défiler ⬆",
+        "Scroll ⬇" => "Défiler ⬇",
+        "Scroll ⬅" => "Défiler ⬅",
+        "Scroll ➡" => "Défiler ➡",
+        _ => return None,
+    };
+    Some(translated)
+}
+
+/// Adds a system font with Hangul coverage as a fallback, since egui's bundled
+/// fonts only cover Latin, Greek and Cyrillic.
+pub fn add_fallback_fonts(fonts: &mut egui::FontDefinitions) {
+    const FALLBACK_FONTS: &[&str] = &[
+        "C:\\Windows\\Fonts\\malgun.ttf",
+        "/usr/share/fonts/truetype/nanum/NanumGothic.ttf",
+        "/System/Library/Fonts/AppleSDGothicNeo.ttc",
+    ];
+
+    let Some(data) = FALLBACK_FONTS
+        .iter()
+        .find_map(|path| std::fs::read(path).ok())
+    else {
+        return;
+    };
+
+    fonts.font_data.insert(
+        "hangul_fallback".to_owned(),
+        egui::FontData::from_owned(data).into(),
+    );
+    for family in [egui::FontFamily::Proportional, egui::FontFamily::Monospace] {
+        fonts
+            .families
+            .entry(family)
+            .or_default()
+            .push("hangul_fallback".to_owned());
+    }
+}
@@ -1,99 +1,270 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod animation;
+mod combo;
+mod entry;
+mod gamepad;
+mod gesture;
+mod halo;
+mod heatmap;
+mod icon;
 mod key_hook;
+mod key_names;
+mod keyboard;
+mod keycap;
+mod kle;
+mod locale;
 mod mouse;
+mod mouse_input;
 mod platform;
+mod ripple;
+mod settings;
+mod shortcut;
+mod skin;
+mod theme;
+mod ticker;
 
 use std::{
-    sync::{Arc, Mutex},
+    borrow::Cow,
+    collections::{HashSet, VecDeque},
+    sync::{mpsc, Arc, Mutex},
     thread,
 };
 
 use eframe::{egui, egui::Rgba};
-use indexmap::IndexSet;
 use raw_window_handle::HasWindowHandle;
 use windows::Win32::UI::WindowsAndMessaging::{WM_KEYDOWN, WM_KEYUP, WM_SYSKEYDOWN, WM_SYSKEYUP};
 
-use crate::{key_hook::is_disable_overlay_key_pressed, mouse::draw_mouse};
+use crate::{
+    animation::{AnimatedElement, Animation},
+    combo::{ComboChange, ComboTracker, KeyAction, KeyEvent},
+    entry::{ComboEntry, KeyHold, Stroke, KEY_SEPARATOR},
+    gamepad::{draw_gamepad, Gamepad},
+    gesture::{Gesture, GestureRecognizer, MouseAction, MouseEvent},
+    halo::Halo,
+    heatmap::{draw_heatmap, HeatmapRequest, HeatmapSource, KeyCounts},
+    icon::DeviceIcon,
+    key_hook::is_disable_overlay_key_pressed,
+    keyboard::{draw_keyboard, KeyboardLayout},
+    keycap::Keycaps,
+    locale::tr,
+    mouse::{draw_mouse, WheelState},
+    mouse_input::MouseKeys,
+    ripple::Ripples,
+    settings::{
+        settings_ui, DisplayMode, HistoryDirection, KeyStyle, KeyboardLayoutChoice, Lifetime,
+        Settings, ThemeChoice,
+    },
+    skin::Skin,
+    theme::{TextStyle, Theme, ThemePreset},
+    ticker::{Ticker, TickerItem},
+};
 
 struct App {
-    pressed_keys: Arc<Mutex<IndexSet<u32>>>,
+    key_events: mpsc::Receiver<KeyEvent>,
+    combo: ComboTracker,
     mouse_buttons: Arc<Mutex<[bool; 5]>>,
-    last_combination: IndexSet<u32>,
-    is_key_cleared: bool,
+    wheel: WheelState,
+    mouse_icon: DeviceIcon,
+    gamepad: Gamepad,
+    gamepad_icon: DeviceIcon,
+    // `keycap.svg` artwork, if the user provided one
+    keycap_icon: Option<DeviceIcon>,
+    mouse_events: mpsc::Receiver<MouseEvent>,
+    gestures: GestureRecognizer,
+    // Latest recognized gesture and when it happened
+    gesture: Option<(Gesture, std::time::Instant)>,
+    ripples: Ripples,
+    halo: Halo,
+    current_entry: Option<ComboEntry>,
+    // Replaced entries, newest first. The newest may have been replaced by a
+    // provisional modifier combo only, and is restored when releasing and
+    // re-pressing the whole combo turns out to be a repeat.
+    history: VecDeque<ComboEntry>,
+    // Set while the latest stroke was hidden by shortcuts-only mode
+    is_suppressing: bool,
+    ticker: Ticker,
+    // When the ticker last came back after fading out, for its appear effect
+    ticker_shown: std::time::Instant,
     is_overlay: bool,
-    last_update: std::time::Instant,
     is_show_mouse: bool,
+    is_show_gamepad: bool,
     is_outline: bool,
+    is_settings_open: bool,
+    // Whether the open settings window was fitted to its contents
+    is_settings_sized: bool,
+    settings: Settings,
+    skin: Option<Skin>,
+    // Skin name last passed to `Skin::load`, so a failing pack isn't retried
+    // every frame
+    loaded_skin: Option<String>,
+    theme: Theme,
+    // Theme choice and OS theme the current theme was loaded for
+    loaded_theme: Option<(ThemeChoice, Option<egui::Theme>)>,
+    keyboard: Option<KeyboardLayout>,
+    // Layout and KLE path the keyboard view was last loaded from
+    loaded_keyboard: Option<(KeyboardLayoutChoice, String)>,
+    heatmap_session: heatmap::Session,
+    // Sum of the saved sessions, read when first shown
+    saved_counts: Option<KeyCounts>,
 }
 
 impl App {
     fn new() -> Self {
-        let pressed_keys = Arc::new(Mutex::new(IndexSet::new()));
-        let pressed_keys_clone = pressed_keys.clone();
+        let (key_sender, key_events) = mpsc::channel();
+        let mouse_key_sender = key_sender.clone();
+        let (mouse_event_sender, mouse_events) = mpsc::channel();
         let mouse_buttons: Arc<Mutex<[bool; 5]>> = Arc::new(Mutex::new([false; 5]));
         let mouse_buttons_clone = mouse_buttons.clone();
 
         thread::spawn(move || unsafe {
-            key_hook::register_hook(move |vk, msg| {
-                if msg == WM_KEYDOWN || msg == WM_SYSKEYDOWN {
-                    let mut lock = pressed_keys_clone.lock().unwrap();
-                    lock.insert(vk);
+            key_hook::register_hook(move |vk, scan_code, msg| {
+                let action = if msg == WM_KEYDOWN || msg == WM_SYSKEYDOWN {
+                    KeyAction::Down
                 } else if msg == WM_KEYUP || msg == WM_SYSKEYUP {
-                    let mut lock = pressed_keys_clone.lock().unwrap();
-                    lock.swap_remove(&vk);
-                }
+                    KeyAction::Up
+                } else {
+                    return;
+                };
+                let _ = key_sender.send(KeyEvent {
+                    vk,
+                    scan_code,
+                    action,
+                    time: std::time::Instant::now(),
+                });
             });
         });
         // Mouse hook thread
         thread::spawn(move || unsafe {
             use windows::Win32::UI::WindowsAndMessaging::*;
-            key_hook::register_mouse_hook(move |msg, _x, _y, data| {
-                let mut state = mouse_buttons_clone.lock().unwrap();
-                match msg {
-                    WM_LBUTTONDOWN => state[0] = true,
-                    WM_LBUTTONUP => state[0] = false,
-                    WM_RBUTTONDOWN => state[1] = true,
-                    WM_RBUTTONUP => state[1] = false,
-                    WM_MBUTTONDOWN => state[2] = true,
-                    WM_MBUTTONUP => state[2] = false,
-                    WM_XBUTTONDOWN => {
-                        let which = (data >> 16) & 0xFFFF;
-                        if which == 1 {
-                            state[3] = true;
-                        } else if which == 2 {
-                            state[4] = true;
-                        }
+            let mut mouse_keys =
+                MouseKeys::new((GetSystemMetrics(SM_CXDRAG), GetSystemMetrics(SM_CYDRAG)));
+            key_hook::register_mouse_hook(move |msg, x, y, data| {
+                let time = std::time::Instant::now();
+                let xbutton = match (data >> 16) & 0xFFFF {
+                    1 => Some(3),
+                    2 => Some(4),
+                    _ => None,
+                };
+                let (button, is_down) = match msg {
+                    WM_LBUTTONDOWN => (Some(0), true),
+                    WM_LBUTTONUP => (Some(0), false),
+                    WM_RBUTTONDOWN => (Some(1), true),
+                    WM_RBUTTONUP => (Some(1), false),
+                    WM_MBUTTONDOWN => (Some(2), true),
+                    WM_MBUTTONUP => (Some(2), false),
+                    WM_XBUTTONDOWN => (xbutton, true),
+                    WM_XBUTTONUP => (xbutton, false),
+                    _ => (None, false),
+                };
+
+                // Wheel delta is the signed high word of mouseData
+                let delta = (data >> 16) as i16 as i32;
+
+                let action = match (button, is_down) {
+                    (Some(button), true) => Some(MouseAction::Down(button)),
+                    (Some(button), false) => Some(MouseAction::Up(button)),
+                    (None, _) => match msg {
+                        WM_MOUSEMOVE => Some(MouseAction::Move),
+                        WM_MOUSEWHEEL | WM_MOUSEHWHEEL => Some(MouseAction::Wheel {
+                            delta,
+                            is_horizontal: msg == WM_MOUSEHWHEEL,
+                        }),
+                        _ => None,
+                    },
+                };
+                if let Some(action) = action {
+                    let _ = mouse_event_sender.send(MouseEvent { action, x, y, time });
+                }
+
+                let events = if let Some(button) = button {
+                    mouse_buttons_clone.lock().unwrap()[button] = is_down;
+                    if is_down {
+                        vec![mouse_keys.button_down(button, x, y, time)]
+                    } else {
+                        vec![mouse_keys.button_up(button, time)]
                     }
-                    WM_XBUTTONUP => {
-                        let which = (data >> 16) & 0xFFFF;
-                        if which == 1 {
-                            state[3] = false;
-                        } else if which == 2 {
-                            state[4] = false;
-                        }
+                } else {
+                    match msg {
+                        WM_MOUSEMOVE => mouse_keys.moved(x, y, time),
+                        WM_MOUSEWHEEL => mouse_keys.wheel(delta, false, time),
+                        WM_MOUSEHWHEEL => mouse_keys.wheel(delta, true, time),
+                        _ => Vec::new(),
                     }
-                    _ => {}
+                };
+                for event in events {
+                    let _ = mouse_key_sender.send(event);
                 }
             });
         });
+        let mut settings = Settings::default();
+        let mouse_icon = mouse::load_mouse_icon().unwrap_or_else(|err| {
+            settings.icon_errors.push(format!("mouse.svg: {err}"));
+            mouse::builtin_mouse_icon()
+        });
+        let gamepad_icon = gamepad::load_gamepad_icon().unwrap_or_else(|err| {
+            settings.icon_errors.push(format!("gamepad.svg: {err}"));
+            gamepad::builtin_gamepad_icon()
+        });
+        let keycap_icon = keycap::load_keycap_icon().unwrap_or_else(|err| {
+            settings.icon_errors.push(format!("keycap.svg: {err}"));
+            None
+        });
+        let gestures = unsafe {
+            use windows::Win32::UI::WindowsAndMessaging::*;
+            GestureRecognizer::new(
+                (GetSystemMetrics(SM_CXDRAG), GetSystemMetrics(SM_CYDRAG)),
+                (
+                    GetSystemMetrics(SM_CXDOUBLECLK),
+                    GetSystemMetrics(SM_CYDOUBLECLK),
+                ),
+            )
+        };
         Self {
-            pressed_keys,
-            last_combination: IndexSet::new(),
-            is_key_cleared: false,
+            key_events,
+            mouse_events,
+            gestures,
+            gesture: None,
+            ripples: Ripples::default(),
+            halo: Halo::default(),
+            combo: ComboTracker::default(),
+            current_entry: None,
+            history: VecDeque::new(),
+            is_suppressing: false,
+            ticker: Ticker::default(),
+            ticker_shown: std::time::Instant::now(),
             is_overlay: false,
-            last_update: std::time::Instant::now(),
             is_show_mouse: true,
+            is_show_gamepad: true,
             is_outline: true,
+            is_settings_open: false,
+            is_settings_sized: false,
+            settings,
+            skin: None,
+            loaded_skin: None,
+            theme: Theme::preset(ThemePreset::Dark),
+            loaded_theme: None,
+            keyboard: None,
+            loaded_keyboard: None,
+            heatmap_session: heatmap::Session::default(),
+            saved_counts: None,
             mouse_buttons,
+            wheel: WheelState::default(),
+            mouse_icon,
+            gamepad: Gamepad::default(),
+            gamepad_icon,
+            keycap_icon,
         }
     }
 }
 
 const TITLE_BAR_HEIGHT: f32 = 32.0;
 const TITLE_SIDE_PADDING: f32 = 10.0;
+// Title of the fullscreen click-through window for click ripples and the
+// cursor halo, used to find it for placement
+const POINTER_OVERLAY_TITLE: &str = "Keyboim Pointer Overlay";
 
-fn title_bar_ui(ui: &mut egui::Ui, title_bar_rect: egui::Rect, title: &str) {
+fn title_bar_ui(ui: &mut egui::Ui, title_bar_rect: egui::Rect, title: &str, theme: &Theme) {
     use egui::{Id, PointerButton, Sense};
 
     let bar_resp = ui.interact(
@@ -105,12 +276,13 @@ fn title_bar_ui(ui: &mut egui::Ui, title_bar_rect: egui::Rect, title: &str) {
     let p = ui.painter_at(title_bar_rect);
     let visuals = ui.visuals();
 
-    let fill = visuals.window_fill();
+    let fill = theme.background;
     let stroke = visuals.window_stroke();
 
+    let radius = theme.corner_radius.round() as u8;
     let corner_radius = egui::CornerRadius {
-        nw: 4,
-        ne: 4,
+        nw: radius,
+        ne: radius,
         sw: 0,
         se: 0,
     };
@@ -139,17 +311,18 @@ fn title_bar_ui(ui: &mut egui::Ui, title_bar_rect: egui::Rect, title: &str) {
     }
 }
 
-fn background_ui(ui: &mut egui::Ui, rect: egui::Rect) {
+fn background_ui(ui: &mut egui::Ui, rect: egui::Rect, theme: &Theme) {
     let p = ui.painter_at(rect);
     let visuals = ui.visuals();
 
+    let radius = theme.corner_radius.round() as u8;
     let corner_radius = egui::CornerRadius {
         nw: 0,
         ne: 0,
-        sw: 4,
-        se: 4,
+        sw: radius,
+        se: radius,
     };
-    p.rect_filled(rect, corner_radius, visuals.window_fill());
+    p.rect_filled(rect, corner_radius, theme.background);
     p.rect_stroke(
         rect,
         corner_radius,
@@ -158,40 +331,635 @@ fn background_ui(ui: &mut egui::Ui, rect: egui::Rect) {
     );
 }
 
-fn outlined_text(
+// Draws `text` with its top-left corner at `pos`, outlined when the style
+// has an outline
+fn draw_text(ui: &mut egui::Ui, text: &str, pos: egui::Pos2, style: &TextStyle) {
+    if let Some((outline_color, outline_thickness)) = style.outline {
+        let diagonal = outline_thickness * 0.7071;
+        let offsets = [
+            egui::Vec2::new(-outline_thickness, 0.0),
+            egui::Vec2::new(outline_thickness, 0.0),
+            egui::Vec2::new(0.0, -outline_thickness),
+            egui::Vec2::new(0.0, outline_thickness),
+            egui::Vec2::new(-diagonal, -diagonal),
+            egui::Vec2::new(diagonal, -diagonal),
+            egui::Vec2::new(-diagonal, diagonal),
+            egui::Vec2::new(diagonal, diagonal),
+        ];
+
+        for offset in offsets {
+            ui.painter().text(
+                pos + offset,
+                egui::Align2::LEFT_TOP,
+                text,
+                style.font.clone(),
+                outline_color,
+            );
+        }
+    }
+
+    ui.painter().text(
+        pos,
+        egui::Align2::LEFT_TOP,
+        text,
+        style.font.clone(),
+        style.color,
+    );
+}
+
+const HOLD_INDICATOR_HEIGHT: f32 = 16.0;
+
+// Fade-out progress: 0 for the display time, then rising linearly to 1 over
+// the fade time
+fn fade_progress(since: std::time::Instant, settings: &Settings) -> f32 {
+    let elapsed = since.elapsed().as_secs_f32() - settings.display_time;
+    let faded = if settings.fade_time > 0.0 {
+        elapsed / settings.fade_time
+    } else if elapsed > 0.0 {
+        1.0
+    } else {
+        0.0
+    };
+    faded.clamp(0.0, 1.0)
+}
+
+fn entry_fade(entry: &ComboEntry, is_current: bool, settings: &Settings) -> f32 {
+    match settings.lifetime {
+        Lifetime::AlwaysLast if is_current => 0.0,
+        Lifetime::WhileHeld => match entry.released() {
+            Some(released) => fade_progress(released, settings),
+            None => 0.0,
+        },
+        _ => fade_progress(entry.updated, settings),
+    }
+}
+
+// Draws `add_contents` in the pose `animation` has reached, passing it the
+// alpha to draw with. `pivot` is where pops scale around, relative to the
+// cursor. Nothing is drawn or laid out while the element is invisible.
+fn draw_animated(
+    ui: &mut egui::Ui,
+    animation: &Animation,
+    shown: std::time::Instant,
+    fade: f32,
+    bounced: Option<std::time::Instant>,
+    pivot: egui::Vec2,
+    add_contents: impl FnOnce(&mut egui::Ui, u8),
+) {
+    let pose = animation.pose(
+        shown.elapsed().as_secs_f32(),
+        fade,
+        bounced.map(|time| time.elapsed().as_secs_f32()),
+        ui.cursor().min + pivot,
+        ui.ctx().screen_rect().size(),
+    );
+    let alpha = pose.alpha();
+    if alpha > 0 {
+        ui.with_visual_transform(pose.transform, |ui| add_contents(ui, alpha));
+    }
+}
+
+fn draw_entry(
     ui: &mut egui::Ui,
-    text: &str,
-    pos: egui::Pos2,
-    font_size: f32,
-    text_color: egui::Color32,
-    outline_color: egui::Color32,
-    outline_thickness: f32,
+    entry: &mut ComboEntry,
+    settings: &Settings,
+    keycaps: &mut Keycaps,
+    style: &TextStyle,
+    alpha: u8,
 ) {
-    let font = egui::FontId::proportional(font_size);
-    let diagonal = outline_thickness as f32 * 0.7071;
-    let offsets = [
-        egui::Vec2::new(-outline_thickness, 0.0),
-        egui::Vec2::new(outline_thickness, 0.0),
-        egui::Vec2::new(0.0, -outline_thickness),
-        egui::Vec2::new(0.0, outline_thickness),
-        egui::Vec2::new(-diagonal, -diagonal),
-        egui::Vec2::new(diagonal, -diagonal),
-        egui::Vec2::new(-diagonal, diagonal),
-        egui::Vec2::new(diagonal, diagonal),
-    ];
-
-    for offset in offsets {
-        ui.painter().text(
-            pos + offset,
-            egui::Align2::LEFT_TOP,
-            text,
-            font.clone(),
-            outline_color,
+    let font_size = style.font.size;
+
+    let origin = ui.cursor().min;
+    let mut x = origin.x;
+    let mut height: f32 = 0.0;
+
+    // A skin with keycap images replaces the text style too
+    let is_keycaps =
+        settings.key_style == KeyStyle::Keycaps || keycaps.skin.is_some_and(Skin::has_keycaps);
+
+    for segment in entry.segments(settings) {
+        let size = match segment.hold {
+            Some(hold) if is_keycaps => {
+                let pos = egui::pos2(x, origin.y);
+                let is_pressed = hold.released.is_none();
+                keycaps.draw(ui, pos, &segment.text, style, is_pressed, alpha)
+            }
+            // Keycaps stand apart without a "+" between them
+            None if is_keycaps && segment.text == KEY_SEPARATOR => {
+                egui::vec2(font_size * 0.15, 0.0)
+            }
+            _ => {
+                let size = ui
+                    .painter()
+                    .layout_no_wrap(segment.text.clone(), style.font.clone(), style.color)
+                    .size();
+                // Center the text on the caps when mixed with keycaps
+                let y = if is_keycaps {
+                    origin.y + (keycap::cap_height(font_size) - size.y) / 2.0
+                } else {
+                    origin.y
+                };
+                draw_text(ui, &segment.text, egui::pos2(x, y), style);
+                if is_keycaps {
+                    egui::vec2(size.x, 0.0)
+                } else {
+                    size
+                }
+            }
+        };
+
+        if settings.is_show_hold {
+            if let Some(hold) = segment.hold {
+                let rect = egui::Rect::from_min_size(
+                    egui::pos2(x, origin.y + size.y),
+                    egui::vec2(size.x, HOLD_INDICATOR_HEIGHT),
+                );
+                draw_hold_indicator(ui, rect, &hold, settings, alpha);
+            }
+        }
+
+        x += size.x;
+        height = height.max(size.y);
+    }
+
+    if settings.is_show_hold {
+        height += HOLD_INDICATOR_HEIGHT;
+    }
+    ui.allocate_space(egui::vec2(x - origin.x, height));
+}
+
+fn draw_gesture(ui: &mut egui::Ui, gesture: &Gesture, settings: &Settings, style: &TextStyle) {
+    ui.label(
+        egui::RichText::new(gesture.label(settings.locale()))
+            .font(style.font.clone())
+            .color(style.color),
+    );
+}
+
+// Draws the newest ticker items right-aligned, letting older text run off the
+// left edge
+fn draw_ticker(ui: &mut egui::Ui, ticker: &Ticker, style: &TextStyle, alpha: u8) {
+    let chip_font = egui::FontId::new(style.font.size * 0.5, style.font.family.clone());
+    let chip_padding = egui::vec2(8.0, 4.0);
+    let text_color = style.color;
+
+    let origin = ui.cursor().min;
+    let width = ui.available_width();
+    let line_height = ui
+        .painter()
+        .layout_no_wrap("A".to_string(), style.font.clone(), text_color)
+        .size()
+        .y;
+    let clip = egui::Rect::from_min_size(origin, egui::vec2(width, line_height));
+
+    ui.scope(|ui| {
+        ui.set_clip_rect(clip.intersect(ui.clip_rect()));
+
+        let mut right = clip.right();
+        for item in ticker.items().rev() {
+            if right <= clip.left() {
+                break;
+            }
+            match item {
+                TickerItem::Text(text) => {
+                    let galley =
+                        ui.painter()
+                            .layout_no_wrap(text.clone(), style.font.clone(), text_color);
+                    right -= galley.size().x;
+                    draw_text(ui, text, egui::pos2(right, origin.y), style);
+                }
+                TickerItem::Chip(label) => {
+                    let galley =
+                        ui.painter()
+                            .layout_no_wrap(label.clone(), chip_font.clone(), text_color);
+                    let chip_size = galley.size() + chip_padding * 2.0;
+                    let chip_rect = egui::Rect::from_min_size(
+                        egui::pos2(
+                            right - chip_size.x - 4.0,
+                            origin.y + (line_height - chip_size.y) / 2.0,
+                        ),
+                        chip_size,
+                    );
+                    let fill = egui::Color32::from_white_alpha(alpha) * ui.visuals().faint_bg_color;
+                    ui.painter().rect(
+                        chip_rect,
+                        6.0,
+                        fill,
+                        egui::Stroke::new(1.0, text_color),
+                        egui::StrokeKind::Inside,
+                    );
+                    ui.painter()
+                        .galley(chip_rect.min + chip_padding, galley, text_color);
+                    right = chip_rect.left() - 4.0;
+                }
+            }
+        }
+    });
+
+    ui.allocate_space(egui::vec2(width, line_height));
+}
+
+// Progress bar toward the tap/hold threshold while the key is down, and a
+// "tap"/"hold" tag with the duration once it is released
+fn draw_hold_indicator(
+    ui: &egui::Ui,
+    rect: egui::Rect,
+    hold: &KeyHold,
+    settings: &Settings,
+    alpha: u8,
+) {
+    let now = std::time::Instant::now();
+    let threshold = std::time::Duration::from_secs_f32(settings.hold_threshold);
+    let is_hold = hold.is_hold(now, threshold);
+    let tint = egui::Color32::from_white_alpha(alpha);
+    let color = if is_hold {
+        tint * ui.visuals().selection.bg_fill
+    } else {
+        tint * ui.visuals().weak_text_color()
+    };
+    let painter = ui.painter();
+
+    match hold.released {
+        None => {
+            let bar = egui::Rect::from_min_size(
+                rect.min + egui::vec2(2.0, 2.0),
+                egui::vec2(rect.width() - 4.0, 4.0),
+            );
+            let progress =
+                (hold.duration(now).as_secs_f32() / settings.hold_threshold).clamp(0.0, 1.0);
+            painter.rect_filled(bar, 2.0, tint * ui.visuals().extreme_bg_color);
+            painter.rect_filled(
+                bar.with_max_x(bar.min.x + bar.width() * progress),
+                2.0,
+                color,
+            );
+        }
+        Some(_) => {
+            let kind = if is_hold { "hold" } else { "tap" };
+            let text = format!(
+                "{} {:.2}s",
+                tr(settings.locale(), kind),
+                hold.duration(now).as_secs_f32()
+            );
+            painter.text(
+                rect.center_top(),
+                egui::Align2::CENTER_TOP,
+                text,
+                egui::FontId::proportional(12.0),
+                color,
+            );
+        }
+    }
+}
+
+impl App {
+    // Mouse input only joins a combo while a keyboard key is held, so plain
+    // clicks and scrolling don't flood the overlay
+    fn accepts_mouse_key(&self) -> bool {
+        self.settings.is_mouse_combos
+            && self
+                .combo
+                .held()
+                .iter()
+                .any(|&vk| !key_names::is_mouse_key(vk))
+    }
+
+    // Shortcuts-only mode drops plain typing before it reaches any entry. That
+    // includes a plain key after a sequence prefix: "Ctrl + K, Ctrl + C" is
+    // still shown, but the P of Ctrl+K then P is text like any other.
+    fn is_hidden_typing(&self, stroke: &Stroke) -> bool {
+        self.settings.is_shortcuts_only && shortcut::is_text_input(&stroke.keys)
+    }
+
+    fn feed_ticker(&mut self, vk: u32, stroke: &Stroke, time: std::time::Instant) {
+        if self.combo.is_provisional() {
+            return;
+        }
+        if self.ticker.is_empty() || self.ticker_fade() >= 1.0 {
+            self.ticker_shown = time;
+        }
+
+        if shortcut::is_text_input(&stroke.keys) {
+            match vk {
+                0x08 => self.ticker.backspace(time),
+                0x0D | key_names::VK_NUMPAD_ENTER => self.ticker.type_text("↩ ", time),
+                0x09 => self.ticker.type_text("→ ", time),
+                _ => {
+                    if let Some(text) =
+                        unsafe { key_hook::vk_to_char(vk, &stroke.keys, stroke.layout) }
+                    {
+                        self.ticker.type_text(&text, time);
+                    }
+                }
+            }
+        } else {
+            let mut keys = stroke.keys.clone();
+            let label = key_hook::key_combination_labels(&mut keys, stroke.layout, &self.settings)
+                .into_iter()
+                .map(|(_, label)| label)
+                .collect::<Vec<_>>()
+                .join(" + ");
+            self.ticker.push_chip(label, time);
+        }
+    }
+
+    fn add_stroke(&mut self, change: ComboChange, stroke: Stroke, time: std::time::Instant) {
+        entry::add_stroke(
+            &mut self.current_entry,
+            &mut self.history,
+            change,
+            stroke,
+            time,
+            &self.settings,
         );
     }
 
-    ui.painter()
-        .text(pos, egui::Align2::LEFT_TOP, text, font, text_color);
+    fn ticker_fade(&self) -> f32 {
+        match self.settings.lifetime {
+            Lifetime::AlwaysLast => 0.0,
+            _ => fade_progress(self.ticker.updated, &self.settings),
+        }
+    }
+
+    // Current entry and the history as a stack, each fading on its own
+    fn draw_history(&mut self, ui: &mut egui::Ui) {
+        let history_len = self.settings.history_length.min(self.history.len());
+        let mut entries: Vec<(&mut ComboEntry, bool)> = self
+            .current_entry
+            .iter_mut()
+            .map(|entry| (entry, true))
+            .chain(
+                self.history
+                    .iter_mut()
+                    .take(history_len)
+                    .map(|entry| (entry, false)),
+            )
+            .collect();
+        if self.settings.history_direction == HistoryDirection::NewestAtBottom {
+            entries.reverse();
+        }
+
+        let animation = self.settings.animation(AnimatedElement::Combo);
+        ui.vertical(|ui| {
+            for (entry, is_current) in entries {
+                let fade = entry_fade(entry, is_current, &self.settings);
+                let font_size = if is_current {
+                    self.theme.font_size
+                } else {
+                    self.theme.history_font_size
+                };
+                let bounced = (entry.presses > 1).then_some(entry.pressed);
+                draw_animated(
+                    ui,
+                    animation,
+                    entry.started,
+                    fade,
+                    bounced,
+                    egui::vec2(0.0, font_size / 2.0),
+                    |ui, alpha| {
+                        let style = self.theme.text_style(font_size, alpha, self.is_outline);
+                        let mut keycaps = Keycaps {
+                            skin: self.skin.as_ref(),
+                            icon: self.keycap_icon.as_mut(),
+                            theme: &self.theme,
+                        };
+                        draw_entry(ui, entry, &self.settings, &mut keycaps, &style, alpha);
+                    },
+                );
+            }
+        });
+    }
+
+    fn sync_skin(&mut self, ctx: &egui::Context) {
+        if self.settings.skin == self.loaded_skin {
+            return;
+        }
+        self.loaded_skin = self.settings.skin.clone();
+        self.settings.skin_error = None;
+        self.skin = match &self.settings.skin {
+            Some(name) => match Skin::load(ctx, name) {
+                Ok(skin) => Some(skin),
+                Err(err) => {
+                    self.settings.skin_error = Some(err.to_string());
+                    None
+                }
+            },
+            None => None,
+        };
+    }
+
+    fn sync_theme(&mut self, ctx: &egui::Context) {
+        let system = ctx.system_theme();
+        let choice = (self.settings.theme.clone(), system);
+        if self.loaded_theme.as_ref() == Some(&choice) {
+            return;
+        }
+        self.settings.theme_error = None;
+        let system_preset = Theme::preset(ThemePreset::for_system(system));
+        self.theme = match &choice.0 {
+            ThemeChoice::System => system_preset,
+            ThemeChoice::Preset(preset) => Theme::preset(*preset),
+            ThemeChoice::Custom(name) => Theme::load(name, system).unwrap_or_else(|err| {
+                self.settings.theme_error = Some(err.to_string());
+                system_preset
+            }),
+        };
+        self.theme.apply(ctx);
+        self.loaded_theme = Some(choice);
+    }
+
+    fn sync_keyboard_layout(&mut self) {
+        let choice = (
+            self.settings.keyboard_layout,
+            self.settings.kle_path.clone(),
+        );
+        if self.loaded_keyboard.as_ref() == Some(&choice) {
+            return;
+        }
+        self.settings.keyboard_error = None;
+        self.keyboard = match choice.0 {
+            KeyboardLayoutChoice::Builtin(layout) => Some(KeyboardLayout::builtin(layout)),
+            KeyboardLayoutChoice::File if choice.1.trim().is_empty() => None,
+            KeyboardLayoutChoice::File => match KeyboardLayout::load(choice.1.trim().as_ref()) {
+                Ok(layout) => Some(layout),
+                Err(err) => {
+                    self.settings.keyboard_error = Some(err.to_string());
+                    None
+                }
+            },
+        };
+        self.loaded_keyboard = Some(choice);
+    }
+
+    fn update_heatmap(&mut self) {
+        if self.settings.heatmap_source == HeatmapSource::AllSessions && self.saved_counts.is_none()
+        {
+            let (counts, errors) = self
+                .heatmap_session
+                .load_saved()
+                .unwrap_or_else(|err| (KeyCounts::default(), vec![err]));
+            if !errors.is_empty() {
+                let errors: Vec<String> = errors.iter().map(ToString::to_string).collect();
+                self.settings.heatmap_status = Some(errors.join("\n"));
+            }
+            self.saved_counts = Some(counts);
+        }
+
+        let locale = self.settings.locale();
+        let status = match self.settings.heatmap_request.take() {
+            Some(HeatmapRequest::SaveSession) => Some(match self.heatmap_session.save() {
+                Ok(path) => {
+                    tr(locale, "Saved to {path}").replace("{path}", &path.to_string_lossy())
+                }
+                Err(err) => err.to_string(),
+            }),
+            Some(HeatmapRequest::ExportPng) => Some(match &self.keyboard {
+                Some(layout) => {
+                    match heatmap::export_png(
+                        layout,
+                        &self.heatmap_counts(),
+                        self.settings.color_scale,
+                    ) {
+                        Ok(path) => {
+                            tr(locale, "Saved to {path}").replace("{path}", &path.to_string_lossy())
+                        }
+                        Err(err) => err.to_string(),
+                    }
+                }
+                None => tr(locale, "No keyboard layout loaded").to_string(),
+            }),
+            Some(HeatmapRequest::ResetSession) => {
+                self.heatmap_session.counts.clear();
+                None
+            }
+            None => return,
+        };
+        self.settings.heatmap_status = status;
+    }
+
+    fn heatmap_counts(&self) -> Cow<'_, KeyCounts> {
+        match (&self.settings.heatmap_source, &self.saved_counts) {
+            (HeatmapSource::AllSessions, Some(saved)) => {
+                let mut counts = saved.clone();
+                counts.merge(&self.heatmap_session.counts);
+                Cow::Owned(counts)
+            }
+            _ => Cow::Borrowed(&self.heatmap_session.counts),
+        }
+    }
+
+    // Scan codes of the held keys. Shortcuts-only mode shows which modifiers
+    // are down but not what is being typed.
+    fn pressed_scan_codes(&self) -> HashSet<u32> {
+        let held = self.combo.held();
+        let is_hidden = self.settings.is_shortcuts_only && shortcut::is_text_input(held);
+        held.iter()
+            .filter(|&&vk| !is_hidden || shortcut::Modifier::from_vk(vk).is_some())
+            .filter_map(|&vk| self.combo.scan_code(vk))
+            .collect()
+    }
+
+    fn process_mouse_events(&mut self) {
+        while let Ok(event) = self.mouse_events.try_recv() {
+            if let MouseAction::Wheel {
+                delta,
+                is_horizontal,
+            } = event.action
+            {
+                self.wheel.scroll(delta, is_horizontal, event.time);
+            }
+            match event.action {
+                MouseAction::Down(button) if self.settings.is_show_ripples => {
+                    self.ripples.add(button, event.x, event.y, event.time);
+                }
+                MouseAction::Move if self.settings.is_show_halo => {
+                    self.halo.move_to(event.x, event.y, event.time);
+                }
+                _ => {}
+            }
+            for gesture in self.gestures.handle(&event, &self.settings) {
+                self.gesture = Some((gesture, event.time));
+            }
+        }
+
+        let now = std::time::Instant::now();
+        for gesture in self.gestures.poll(now, &self.settings) {
+            self.gesture = Some((gesture, now));
+        }
+    }
+
+    fn process_key_events(&mut self, frame: &eframe::Frame) {
+        while let Ok(event) = self.key_events.try_recv() {
+            if event.action == KeyAction::Down
+                && key_names::is_mouse_key(event.vk)
+                && !self.accepts_mouse_key()
+            {
+                continue;
+            }
+
+            let is_new_stroke = match self.combo.handle(&event) {
+                Some(change @ (ComboChange::Started | ComboChange::Extended)) => {
+                    let stroke = Stroke::from_tracker(&self.combo, event.time);
+                    if self.is_hidden_typing(&stroke) {
+                        self.is_suppressing = true;
+                    } else {
+                        // Hidden typing isn't counted either, as the heatmap is
+                        // saved to disk
+                        if event.action == KeyAction::Down && self.settings.is_record_heatmap {
+                            if let Some(scan_code) = event.scan_code {
+                                self.heatmap_session.counts.add(scan_code);
+                            }
+                        }
+                        // A provisional combo that was hidden has no entry to extend
+                        let change = if self.is_suppressing {
+                            ComboChange::Started
+                        } else {
+                            change
+                        };
+                        self.is_suppressing = false;
+                        if event.action == KeyAction::Down {
+                            self.feed_ticker(event.vk, &stroke, event.time);
+                        }
+                        self.add_stroke(change, stroke, event.time);
+                    }
+                    true
+                }
+                // Windows auto-repeats held modifiers too, which isn't worth counting
+                Some(ComboChange::Repeated)
+                    if !combo::is_modifier(event.vk) && !self.is_suppressing =>
+                {
+                    if let Some(entry) = &mut self.current_entry {
+                        entry.add_repeat(event.time);
+                    }
+                    // Holding a letter or Backspace keeps typing in the ticker
+                    let stroke = Stroke::from_tracker(&self.combo, event.time);
+                    if shortcut::is_text_input(&stroke.keys) {
+                        self.feed_ticker(event.vk, &stroke, event.time);
+                    }
+                    false
+                }
+                Some(ComboChange::Repeated) => false,
+                None => {
+                    if event.action == KeyAction::Up {
+                        // Keys such as Ctrl can outlive the entry they started in
+                        for entry in self.current_entry.iter_mut().chain(&mut self.history) {
+                            entry.release(event.vk, event.time);
+                        }
+                    }
+                    false
+                }
+            };
+
+            if is_new_stroke && is_disable_overlay_key_pressed(self.combo.held()) {
+                self.is_overlay = false;
+
+                #[cfg(target_os = "windows")]
+                if let Ok(handle) = frame.window_handle() {
+                    platform::disable_click_through_windows(&handle);
+                }
+            }
+        }
+    }
 }
 
 impl eframe::App for App {
@@ -200,6 +968,18 @@ impl eframe::App for App {
     }
 
     fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        self.process_key_events(frame);
+        // Catches layout switches made without a key, such as a change of window
+        unsafe { key_hook::update_active_layout() };
+        self.process_mouse_events();
+        if self.is_show_gamepad {
+            self.gamepad.poll(std::time::Instant::now());
+        }
+        self.sync_skin(ctx);
+        self.sync_theme(ctx);
+        self.sync_keyboard_layout();
+        self.update_heatmap();
+
         egui::CentralPanel::default()
             .frame(egui::Frame::NONE)
             .show(ctx, |ui| {
@@ -220,8 +1000,14 @@ impl eframe::App for App {
                 );
 
                 if !self.is_overlay {
-                    background_ui(ui, remain_rect);
-                    title_bar_ui(ui, title_rect, "Keyboim");
+                    let is_skinned = self
+                        .skin
+                        .as_ref()
+                        .is_some_and(|skin| skin.draw_background(ui, remain_rect));
+                    if !is_skinned {
+                        background_ui(ui, remain_rect, &self.theme);
+                    }
+                    title_bar_ui(ui, title_rect, "Keyboim", &self.theme);
                 }
 
                 let area_rect = egui::Rect::from_min_size(
@@ -242,62 +1028,104 @@ impl eframe::App for App {
                         ui.allocate_space(egui::vec2(ui.available_width(), 0.0));
                         ui.horizontal(|ui| {
                             if self.is_show_mouse {
-                                if let Ok(mouse_buttons) = self.mouse_buttons.lock() {
-                                    draw_mouse(ui, &*mouse_buttons);
+                                ui.vertical(|ui| {
+                                    if let Ok(mouse_buttons) = self.mouse_buttons.lock() {
+                                        let is_skinned = self.skin.as_ref().is_some_and(|skin| {
+                                            skin.draw_mouse(ui, &mouse_buttons, &self.wheel)
+                                        });
+                                        if !is_skinned {
+                                            draw_mouse(
+                                                ui,
+                                                &mut self.mouse_icon,
+                                                &mouse_buttons,
+                                                &self.wheel,
+                                            );
+                                        }
+                                    }
+                                    if let Some((gesture, time)) = &self.gesture {
+                                        if self.settings.is_show_gestures {
+                                            draw_animated(
+                                                ui,
+                                                self.settings.animation(AnimatedElement::Gesture),
+                                                *time,
+                                                fade_progress(*time, &self.settings),
+                                                None,
+                                                egui::vec2(0.0, self.theme.gesture_font_size / 2.0),
+                                                |ui, alpha| {
+                                                    let style = self.theme.text_style(
+                                                        self.theme.gesture_font_size,
+                                                        alpha,
+                                                        false,
+                                                    );
+                                                    draw_gesture(
+                                                        ui,
+                                                        gesture,
+                                                        &self.settings,
+                                                        &style,
+                                                    );
+                                                },
+                                            );
+                                        }
+                                    }
+                                });
+                            }
+                            // Shown only while a controller is connected
+                            if let Some(buttons) = self.gamepad.buttons() {
+                                if self.is_show_gamepad {
+                                    draw_gamepad(ui, &mut self.gamepad_icon, buttons);
                                 }
                             }
-                            if let Ok(pressed_keys) = self.pressed_keys.lock() {
-                                if pressed_keys.is_empty() {
-                                    self.is_key_cleared = true;
-                                } else {
-                                    if pressed_keys.len() > self.last_combination.len()
-                                        || self.is_key_cleared
-                                    {
-                                        self.last_combination = pressed_keys.clone();
-                                        self.last_update = std::time::Instant::now();
-
-                                        if is_disable_overlay_key_pressed(&pressed_keys) {
-                                            self.is_overlay = false;
-
-                                            #[cfg(target_os = "windows")]
-                                            if let Ok(handle) = frame.window_handle() {
-                                                platform::disable_click_through_windows(&handle);
-                                            }
-                                        }
+                            match self.settings.display_mode {
+                                DisplayMode::Combo => {
+                                    if self.current_entry.is_some() {
+                                        self.draw_history(ui);
+                                    } else {
+                                        ui.label("");
+                                    }
+                                }
+                                DisplayMode::Ticker => {
+                                    if !self.ticker.is_empty() {
+                                        // Pops around the newest text at the right
+                                        let pivot = egui::vec2(
+                                            ui.available_width(),
+                                            self.theme.font_size / 2.0,
+                                        );
+                                        draw_animated(
+                                            ui,
+                                            self.settings.animation(AnimatedElement::Ticker),
+                                            self.ticker_shown,
+                                            self.ticker_fade(),
+                                            None,
+                                            pivot,
+                                            |ui, alpha| {
+                                                let style = self.theme.text_style(
+                                                    self.theme.font_size,
+                                                    alpha,
+                                                    self.is_outline,
+                                                );
+                                                draw_ticker(ui, &self.ticker, &style, alpha);
+                                            },
+                                        );
+                                    } else {
+                                        ui.label("");
                                     }
-
-                                    self.is_key_cleared = false;
                                 }
                             }
-                            if !self.last_combination.is_empty() {
-                                let pressed_str =
-                                    key_hook::key_combination_to_string(&mut self.last_combination);
-                                let elapsed = self.last_update.elapsed();
-                                let alpha = (255.0
-                                    * (3.0 - elapsed.as_millis() as f32 / 1000.0).clamp(0.0, 1.0))
-                                    as u8;
-
-                                if self.is_outline {
-                                    outlined_text(
+                        });
+                        if self.settings.is_show_keyboard {
+                            if let Some(keyboard) = &self.keyboard {
+                                if self.settings.is_show_heatmap {
+                                    draw_heatmap(
                                         ui,
-                                        &pressed_str,
-                                        ui.cursor().min,
-                                        56.0,
-                                        egui::Color32::from_white_alpha(alpha)
-                                            * ui.visuals().text_color(),
-                                        egui::Color32::from_black_alpha(alpha / 4),
-                                        2.0,
+                                        keyboard,
+                                        &self.heatmap_counts(),
+                                        self.settings.color_scale,
                                     );
                                 } else {
-                                    ui.label(egui::RichText::new(pressed_str).size(56.0).color(
-                                        egui::Color32::from_white_alpha(alpha)
-                                            * ui.visuals().text_color(),
-                                    ));
+                                    draw_keyboard(ui, keyboard, &self.pressed_scan_codes());
                                 }
-                            } else {
-                                ui.label("");
                             }
-                        });
+                        }
                     });
 
                 if !self.is_overlay {
@@ -314,11 +1142,17 @@ impl eframe::App for App {
                         .fixed_pos(control_rect.min)
                         .default_size(control_rect.size())
                         .show(ui.ctx(), |ui| {
+                            let locale = self.settings.locale();
                             ui.horizontal(|ui| {
-                                ui.checkbox(&mut self.is_outline, "Outline Text");
-                                ui.checkbox(&mut self.is_show_mouse, "Show Mouse");
+                                ui.checkbox(&mut self.is_outline, tr(locale, "Outline Text"));
+                                ui.checkbox(&mut self.is_show_mouse, tr(locale, "Show Mouse"));
+                                ui.checkbox(&mut self.is_show_gamepad, tr(locale, "Show Gamepad"));
+
+                                if ui.button(tr(locale, "Settings")).clicked() {
+                                    self.is_settings_open = !self.is_settings_open;
+                                }
 
-                                if ui.button("Overlay").clicked() {
+                                if ui.button(tr(locale, "Overlay")).clicked() {
                                     self.is_overlay = true;
 
                                     #[cfg(target_os = "windows")]
@@ -358,8 +1192,96 @@ impl eframe::App for App {
                 }
             });
 
+        if self.is_settings_open {
+            ctx.show_viewport_immediate(
+                egui::ViewportId::from_hash_of("settings"),
+                egui::ViewportBuilder::default()
+                    .with_title(tr(self.settings.locale(), "Keyboim Settings"))
+                    .with_inner_size(egui::vec2(360.0, 240.0)),
+                |ctx, _class| {
+                    let frame = egui::Frame::central_panel(&ctx.style());
+                    let margin = frame.inner_margin.sum();
+                    let content_size = egui::CentralPanel::default()
+                        .frame(frame)
+                        .show(ctx, |ui| {
+                            egui::ScrollArea::vertical()
+                                .show(ui, |ui| settings_ui(ui, &mut self.settings))
+                                .content_size
+                        })
+                        .inner;
+
+                    // Fit the window to the settings once, leaving the user
+                    // free to resize it; taller content scrolls
+                    if !self.is_settings_sized {
+                        self.is_settings_sized = true;
+                        let mut size = (content_size + margin).max(egui::vec2(360.0, 240.0));
+                        if let Some(monitor_size) = ctx.input(|i| i.viewport().monitor_size) {
+                            size = size.min(monitor_size * 0.9);
+                        }
+                        ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(size));
+                    }
+
+                    if ctx.input(|i| i.viewport().close_requested()) {
+                        self.is_settings_open = false;
+                    }
+                },
+            );
+        } else {
+            self.is_settings_sized = false;
+        }
+
+        if self.settings.is_show_ripples || self.settings.is_show_halo {
+            ctx.show_viewport_immediate(
+                egui::ViewportId::from_hash_of("pointer_overlay"),
+                egui::ViewportBuilder::default()
+                    .with_title(POINTER_OVERLAY_TITLE)
+                    .with_decorations(false)
+                    .with_transparent(true)
+                    .with_always_on_top()
+                    .with_mouse_passthrough(true)
+                    .with_taskbar(false)
+                    .with_active(false),
+                |ctx, _class| {
+                    // Sized in physical pixels by the platform layer, since a
+                    // position in points depends on the monitor it's read on
+                    let origin = platform::cover_virtual_screen(POINTER_OVERLAY_TITLE);
+                    egui::CentralPanel::default()
+                        .frame(egui::Frame::NONE)
+                        .show(ctx, |ui| {
+                            let Some(origin) = origin else {
+                                return;
+                            };
+                            let now = std::time::Instant::now();
+                            if self.settings.is_show_halo {
+                                self.halo.paint(
+                                    ui.painter(),
+                                    origin,
+                                    self.settings.halo_color,
+                                    self.settings.halo_radius,
+                                    self.settings.is_show_trail,
+                                    now,
+                                );
+                            }
+                            self.ripples.paint(
+                                ui.painter(),
+                                origin,
+                                &self.settings.ripple_colors,
+                                now,
+                            );
+                        });
+                },
+            );
+        }
+
         ctx.request_repaint();
     }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        // Recording was opted into, so keep what it gathered
+        if self.settings.is_record_heatmap && !self.heatmap_session.counts.is_empty() {
+            let _ = self.heatmap_session.save();
+        }
+    }
 }
 
 const ICON_DATA: &[u8] = include_bytes!("icon.bin");
@@ -383,5 +1305,12 @@ fn main() -> eframe::Result<()> {
         ..Default::default()
     };
 
-    eframe::run_native("Keyboim", options, Box::new(|_cc| Ok(Box::new(App::new()))))
+    eframe::run_native(
+        "Keyboim",
+        options,
+        Box::new(|cc| {
+            theme::install_fonts(&cc.egui_ctx, None);
+            Ok(Box::new(App::new()))
+        }),
+    )
 }
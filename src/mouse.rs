@@ -1,234 +1,145 @@
-// Simple ear-clipping triangulation for a simple (non self-intersecting) polygon.
-// Returns indices into the points slice (triplets). Ensures counter-clockwise processing.
-fn triangulate_polygon(points: &[egui::Pos2]) -> Vec<[usize; 3]> {
-    let n = points.len();
-    if n < 3 {
-        return Vec::new();
-    }
-
-    // Compute signed area to determine winding (shoelace)
-    let area: f32 = points.iter().enumerate().fold(0.0, |acc, (i, p)| {
-        let q = points[(i + 1) % n];
-        acc + (p.x * q.y - q.x * p.y)
-    });
-    let ccw = area > 0.0;
-
-    // Work list of vertex indices in current polygon
-    let mut v: Vec<usize> = if ccw {
-        (0..n).collect()
-    } else {
-        (0..n).rev().collect()
-    };
-    let mut triangles = Vec::with_capacity(n - 2);
-
-    // Helpers
-    let is_point_in_triangle =
-        |a: egui::Pos2, b: egui::Pos2, c: egui::Pos2, p: egui::Pos2| -> bool {
-            // Barycentric technique
-            let v0 = egui::vec2(c.x - a.x, c.y - a.y);
-            let v1 = egui::vec2(b.x - a.x, b.y - a.y);
-            let v2 = egui::vec2(p.x - a.x, p.y - a.y);
-            let den = v0.x * v1.y - v1.x * v0.y;
-            if den.abs() < 1e-6 {
-                return false;
-            }
-            let u = (v2.x * v1.y - v1.x * v2.y) / den;
-            let v_ = (v0.x * v2.y - v2.x * v0.y) / den;
-            u >= 0.0 && v_ >= 0.0 && (u + v_) <= 1.0
-        };
+use std::time::{Duration, Instant};
+
+use crate::{
+    icon::{DeviceIcon, IconError},
+    mouse_input::WHEEL_DELTA,
+};
+
+// Built-in artwork, replaced by `mouse.svg` in the icons directory
+const MOUSE_SVG: &str = include_str!("icons/mouse.svg");
+// SVG regions in the order of the mouse button state
+const MOUSE_REGIONS: [&str; 5] = ["left", "right", "middle", "x1", "x2"];
+
+// How long the scroll indicator stays after the last wheel notch
+const SCROLL_IDLE: Duration = Duration::from_millis(800);
+// Width of the scroll indicator column right of the mouse
+pub const SCROLL_WIDTH: f32 = 30.0;
+
+struct Scroll {
+    delta: i32,
+    last: Instant,
+}
 
-    let is_convex = |a: egui::Pos2, b: egui::Pos2, c: egui::Pos2| -> bool {
-        let cross = (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x);
-        cross > 0.0 // because we enforced CCW order
-    };
+/// Recent scrolling on each wheel axis, vertical then horizontal.
+#[derive(Default)]
+pub struct WheelState {
+    scrolls: [Option<Scroll>; 2],
+}
 
-    let mut guard = 0; // prevent infinite loop on malformed input
-    while v.len() > 3 && guard < 10_000 {
-        guard += 1;
-        let m = v.len();
-        let mut ear_found = false;
-        for i in 0..m {
-            let prev = v[(i + m - 1) % m];
-            let curr = v[i];
-            let next = v[(i + 1) % m];
-            let a = points[prev];
-            let b = points[curr];
-            let c = points[next];
-            if !is_convex(a, b, c) {
-                continue;
+impl WheelState {
+    /// Adds a wheel delta from the hook; positive is up, or right for
+    /// horizontal wheels. Reversing or pausing starts a new count.
+    pub fn scroll(&mut self, delta: i32, is_horizontal: bool, time: Instant) {
+        let scroll = &mut self.scrolls[is_horizontal as usize];
+        match scroll {
+            Some(scroll)
+                if scroll.delta.signum() == delta.signum()
+                    && time.duration_since(scroll.last) < SCROLL_IDLE =>
+            {
+                scroll.delta += delta;
+                scroll.last = time;
             }
-            // Check if any other point lies inside triangle abc
-            let mut any_inside = false;
-            for &other in &v {
-                if other == prev || other == curr || other == next {
-                    continue;
-                }
-                if is_point_in_triangle(a, b, c, points[other]) {
-                    any_inside = true;
-                    break;
-                }
-            }
-            if any_inside {
-                continue;
-            }
-            // It's an ear
-            triangles.push([prev, curr, next]);
-            v.remove(i);
-            ear_found = true;
-            break;
+            _ => *scroll = Some(Scroll { delta, last: time }),
         }
-        if !ear_found {
-            break;
-        } // fallback abort
-    }
-    if v.len() == 3 {
-        triangles.push([v[0], v[1], v[2]]);
     }
-    triangles
-}
 
-fn fill_nonconvex(painter: &egui::Painter, pts: Vec<egui::Pos2>, color: egui::Color32) {
-    if pts.len() < 3 {
-        return;
-    }
-    let tris = triangulate_polygon(&pts);
-    let mut mesh = egui::epaint::Mesh::default();
-    for p in &pts {
-        mesh.colored_vertex(*p, color);
-    }
-    for [a, b, c] in tris {
-        mesh.indices
-            .extend_from_slice(&[a as u32, b as u32, c as u32]);
+    /// Notches scrolled on the axis so far, signed like the deltas, or 0 once
+    /// the wheel has been idle. A partial notch from a high-resolution wheel
+    /// counts as one.
+    pub fn ticks(&self, is_horizontal: bool, now: Instant) -> i32 {
+        match &self.scrolls[is_horizontal as usize] {
+            Some(scroll) if now.duration_since(scroll.last) < SCROLL_IDLE => {
+                let notches = (scroll.delta.abs() + WHEEL_DELTA - 1) / WHEEL_DELTA;
+                notches * scroll.delta.signum()
+            }
+            _ => 0,
+        }
     }
-    painter.add(egui::Shape::Mesh(mesh.into()));
 }
 
-pub fn draw_mouse(ui: &mut egui::Ui, mouse_buttons: &[bool; 5]) {
-    use egui::{Pos2, Stroke};
-
-    // Allocate a square-ish area (can adjust if needed)
-    let desired_size = egui::vec2(64.0, 64.0);
-
-    let (_id, rect) = ui.allocate_space(desired_size);
-    let painter = ui.painter_at(rect);
-
-    // Original SVG coordinate bounds:
-    // x: 96 .. 416 (width 320)
-    // y: 48 .. 464 (height 416)
-    let orig_w = 320.0f32;
-    let orig_h = 416.0f32;
-
-    // Compute uniform scale to fit inside rect with a little padding
-    let pad = 4.0;
-    let avail_w = rect.width() - 2.0 * pad;
-    let avail_h = rect.height() - 2.0 * pad;
-    // Choose uniform scale preserving aspect ratio
-    let scale_x = avail_w / orig_w;
-    let scale_y = avail_h / orig_h;
-    let scale = scale_x.min(scale_y);
-
-    // Top-left of original coordinate system mapped into rect
-    let offset_x = rect.left() + (rect.width() - orig_w * scale) * 0.5 - 96.0 * scale; // subtract min x * scale
-    let offset_y = rect.top() + (rect.height() - orig_h * scale) * 0.5 - 48.0 * scale; // subtract min y * scale
-
-    let transform = |x: f32, y: f32| -> Pos2 {
-        Pos2 {
-            x: offset_x + x * scale,
-            y: offset_y + y * scale,
+/// Draws an arrow and notch count per scrolling axis in `rect`, vertical on
+/// top and horizontal below.
+pub fn draw_scroll(
+    painter: &egui::Painter,
+    rect: egui::Rect,
+    wheel: &WheelState,
+    color: egui::Color32,
+) {
+    let now = Instant::now();
+    for (is_horizontal, center_y) in [
+        (false, rect.center().y - rect.height() / 4.0),
+        (true, rect.center().y + rect.height() / 4.0),
+    ] {
+        let ticks = wheel.ticks(is_horizontal, now);
+        if ticks == 0 {
+            continue;
         }
-    };
 
-    // Colors
-    let fill_color = ui.style().visuals.weak_text_color();
-    let stroke_color = ui.style().visuals.text_color();
-    let stroke_width = (16.0 * scale).clamp(1.5, 4.0); // keep stroke readable at small sizes
-    let stroke = Stroke::new(stroke_width, stroke_color);
-
-    // --- FILL SHAPES (draw only if pressed) ---
-    // Mapping: mouse_buttons[0]=left, [1]=right, [2]=middle
-    // Left button polygon (possibly concave) -> triangulate
-    if mouse_buttons[0] {
-        let pts_raw = [
-            (256.0, 48.0),
-            (96.0, 128.0),
-            (96.0, 256.0),
-            (256.0, 300.0),
-            (256.0, 238.0),
-            (222.0, 238.0),
-            (222.0, 110.0),
-            (256.0, 110.0),
-        ];
-        let pts: Vec<Pos2> = pts_raw.into_iter().map(|(x, y)| transform(x, y)).collect();
-        fill_nonconvex(&painter, pts, fill_color);
-    }
-
-    // Right button polygon -> triangulate
-    if mouse_buttons[1] {
-        let pts_raw = [
-            (256.0, 48.0),
-            (416.0, 128.0),
-            (416.0, 256.0),
-            (256.0, 300.0),
-            (256.0, 238.0),
-            (288.0, 238.0),
-            (288.0, 110.0),
-            (256.0, 110.0),
-        ];
-        let pts: Vec<Pos2> = pts_raw.into_iter().map(|(x, y)| transform(x, y)).collect();
-        fill_nonconvex(&painter, pts, fill_color);
-    }
-
-    // Middle button rectangle (still convex, but reuse mesh path for consistency)
-    if mouse_buttons[2] {
-        let pts_raw = [
-            (222.0, 110.0),
-            (288.0, 110.0),
-            (288.0, 238.0),
-            (222.0, 238.0),
+        let center = egui::pos2(rect.left() + 7.0, center_y);
+        // Unit vector of the scroll direction on screen
+        let dir = match (is_horizontal, ticks > 0) {
+            (false, true) => egui::vec2(0.0, -1.0),
+            (false, false) => egui::vec2(0.0, 1.0),
+            (true, true) => egui::vec2(1.0, 0.0),
+            (true, false) => egui::vec2(-1.0, 0.0),
+        };
+        let side = dir.rot90();
+        let arrow = vec![
+            center + dir * 5.0,
+            center - dir * 4.0 + side * 5.0,
+            center - dir * 4.0 - side * 5.0,
         ];
-        let pts: Vec<Pos2> = pts_raw.into_iter().map(|(x, y)| transform(x, y)).collect();
-        fill_nonconvex(&painter, pts, fill_color);
+        painter.add(egui::Shape::convex_polygon(
+            arrow,
+            color,
+            egui::Stroke::NONE,
+        ));
+        painter.text(
+            egui::pos2(rect.left() + 14.0, center_y),
+            egui::Align2::LEFT_CENTER,
+            ticks.abs().to_string(),
+            egui::FontId::proportional(11.0),
+            color,
+        );
     }
+}
 
-    // --- STROKES (always drawn) ---
-    // Outer outline
-    let outline_pts = [
-        (256.0, 48.0),
-        (96.0, 128.0),
-        (96.0, 360.0),
-        (256.0, 464.0),
-        (416.0, 360.0),
-        (416.0, 128.0),
-        (256.0, 48.0),
-    ];
-    let outline: Vec<Pos2> = outline_pts
-        .into_iter()
-        .map(|(x, y)| transform(x, y))
-        .collect();
-    painter.add(egui::Shape::line(outline, stroke));
-
-    // Horizontal-ish internal poly (the "shoulder" line)
-    let shoulder_pts = [(96.0, 256.0), (256.0, 300.0), (416.0, 256.0)];
-    let shoulder: Vec<Pos2> = shoulder_pts
-        .into_iter()
-        .map(|(x, y)| transform(x, y))
-        .collect();
-    painter.add(egui::Shape::line(shoulder, stroke));
-
-    // Top middle vertical (between top and start of wheel area)
-    painter.line_segment([transform(256.0, 48.0), transform(256.0, 110.0)], stroke);
+/// Mouse artwork from `mouse.svg` in `icon::icons_dir`, or the built-in one.
+pub fn load_mouse_icon() -> Result<DeviceIcon, IconError> {
+    DeviceIcon::load("mouse.svg", MOUSE_SVG, &MOUSE_REGIONS)
+}
 
-    // Bottom middle vertical (below wheel area)
-    painter.line_segment([transform(256.0, 300.0), transform(256.0, 238.0)], stroke);
+pub fn builtin_mouse_icon() -> DeviceIcon {
+    DeviceIcon::parse(MOUSE_SVG, &MOUSE_REGIONS).expect("built-in mouse icon is valid")
+}
 
-    // Rectangle around middle button
-    let rect_pts = [
-        (222.0, 110.0),
-        (288.0, 110.0),
-        (288.0, 238.0),
-        (222.0, 238.0),
-        (222.0, 110.0),
-    ];
-    let rect_path: Vec<Pos2> = rect_pts.into_iter().map(|(x, y)| transform(x, y)).collect();
-    painter.add(egui::Shape::line(rect_path, stroke));
+pub fn draw_mouse(
+    ui: &mut egui::Ui,
+    icon: &mut DeviceIcon,
+    mouse_buttons: &[bool; 5],
+    wheel: &WheelState,
+) {
+    // Allocate a square-ish area for the mouse plus the scroll indicator
+    let desired_size = egui::vec2(64.0 + SCROLL_WIDTH, 64.0);
+
+    let (_id, full_rect) = ui.allocate_space(desired_size);
+    let painter = ui.painter_at(full_rect);
+    let (rect, scroll_rect) = full_rect.split_left_right_at_x(full_rect.left() + 64.0);
+
+    let visuals = ui.visuals();
+    let is_held = |region: &str| {
+        MOUSE_REGIONS
+            .iter()
+            .position(|&name| name == region)
+            .is_some_and(|button| mouse_buttons[button])
+    };
+    icon.paint(
+        &painter,
+        rect.shrink(2.0),
+        is_held,
+        visuals.text_color(),
+        visuals.weak_text_color(),
+    );
+
+    draw_scroll(&painter, scroll_rect, wheel, visuals.text_color());
 }
@@ -0,0 +1,238 @@
+// Converts mouse hook input into pseudo key events so that mouse buttons,
+// drags and wheel notches take part in combo detection.
+
+use std::time::Instant;
+
+use crate::{
+    combo::{KeyAction, KeyEvent},
+    key_names::{
+        VK_MOUSE_BUTTON, VK_MOUSE_DRAG, VK_WHEEL_DOWN, VK_WHEEL_LEFT, VK_WHEEL_RIGHT, VK_WHEEL_UP,
+    },
+};
+
+// One detent of a standard wheel (WHEEL_DELTA)
+pub const WHEEL_DELTA: i32 = 120;
+
+/// Tracks where each held mouse button went down and reports when it has moved
+/// far enough to count as a drag. Shared by the pseudo keys and the gesture
+/// recognizer so both agree on what a drag is.
+pub struct DragDetector {
+    threshold: (i32, i32),
+    press_pos: [Option<(i32, i32)>; 5],
+    is_dragging: [bool; 5],
+}
+
+impl DragDetector {
+    /// `threshold` is the horizontal and vertical distance in pixels a held
+    /// button has to travel before the press counts as a drag, normally
+    /// SM_CXDRAG and SM_CYDRAG.
+    pub fn new(threshold: (i32, i32)) -> Self {
+        Self {
+            threshold,
+            press_pos: [None; 5],
+            is_dragging: [false; 5],
+        }
+    }
+
+    pub fn press(&mut self, button: usize, pos: (i32, i32)) {
+        self.press_pos[button] = Some(pos);
+        self.is_dragging[button] = false;
+    }
+
+    /// Ends the press of `button`, returning where it went down and whether it
+    /// turned into a drag.
+    pub fn release(&mut self, button: usize) -> Option<((i32, i32), bool)> {
+        let pos = self.press_pos[button].take()?;
+        Some((pos, std::mem::take(&mut self.is_dragging[button])))
+    }
+
+    /// Buttons that became drags with the pointer now at `pos`.
+    pub fn moved(&mut self, pos: (i32, i32)) -> Vec<usize> {
+        let mut started = Vec::new();
+        for button in 0..5 {
+            let Some(press) = self.press_pos[button] else {
+                continue;
+            };
+            if self.is_dragging[button] {
+                continue;
+            }
+            if (pos.0 - press.0).abs() > self.threshold.0
+                || (pos.1 - press.1).abs() > self.threshold.1
+            {
+                self.is_dragging[button] = true;
+                started.push(button);
+            }
+        }
+        started
+    }
+
+    pub fn is_dragging(&self, button: usize) -> bool {
+        self.is_dragging[button]
+    }
+}
+
+pub struct MouseKeys {
+    drags: DragDetector,
+    // Leftover delta from high-resolution wheels, per axis
+    wheel_remainder: [i32; 2],
+}
+
+impl MouseKeys {
+    /// `drag_threshold` is passed on to `DragDetector::new`.
+    pub fn new(drag_threshold: (i32, i32)) -> Self {
+        Self {
+            drags: DragDetector::new(drag_threshold),
+            wheel_remainder: [0; 2],
+        }
+    }
+
+    pub fn button_down(&mut self, button: usize, x: i32, y: i32, time: Instant) -> KeyEvent {
+        self.drags.press(button, (x, y));
+        KeyEvent {
+            vk: VK_MOUSE_BUTTON[button],
+            scan_code: None,
+            action: KeyAction::Down,
+            time,
+        }
+    }
+
+    pub fn button_up(&mut self, button: usize, time: Instant) -> KeyEvent {
+        let vk = match self.drags.release(button) {
+            Some((_, true)) => VK_MOUSE_DRAG[button],
+            _ => VK_MOUSE_BUTTON[button],
+        };
+        KeyEvent {
+            vk,
+            scan_code: None,
+            action: KeyAction::Up,
+            time,
+        }
+    }
+
+    /// Emits a replace event for every held button that just became a drag.
+    pub fn moved(&mut self, x: i32, y: i32, time: Instant) -> Vec<KeyEvent> {
+        self.drags
+            .moved((x, y))
+            .into_iter()
+            .map(|button| KeyEvent {
+                vk: VK_MOUSE_DRAG[button],
+                scan_code: None,
+                action: KeyAction::Replace(VK_MOUSE_BUTTON[button]),
+                time,
+            })
+            .collect()
+    }
+
+    /// Emits a press and release per wheel notch. `delta` is the signed wheel
+    /// delta from the hook; positive is up (or right for horizontal wheels).
+    pub fn wheel(&mut self, delta: i32, is_horizontal: bool, time: Instant) -> Vec<KeyEvent> {
+        let axis = is_horizontal as usize;
+        let total = self.wheel_remainder[axis] + delta;
+        let notches = total / WHEEL_DELTA;
+        self.wheel_remainder[axis] = total % WHEEL_DELTA;
+
+        let vk = match (is_horizontal, notches > 0) {
+            (false, true) => VK_WHEEL_UP,
+            (false, false) => VK_WHEEL_DOWN,
+            (true, true) => VK_WHEEL_RIGHT,
+            (true, false) => VK_WHEEL_LEFT,
+        };
+
+        (0..notches.abs())
+            .flat_map(|_| {
+                [KeyAction::Down, KeyAction::Up].map(|action| KeyEvent {
+                    vk,
+                    scan_code: None,
+                    action,
+                    time,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(events: &[KeyEvent]) -> Vec<(u32, KeyAction)> {
+        events
+            .iter()
+            .map(|event| (event.vk, event.action))
+            .collect()
+    }
+
+    #[test]
+    fn pairs_a_press_and_release_per_notch() {
+        let mut mouse = MouseKeys::new((4, 4));
+        let now = Instant::now();
+        assert_eq!(
+            keys(&mouse.wheel(2 * WHEEL_DELTA, false, now)),
+            [
+                (VK_WHEEL_UP, KeyAction::Down),
+                (VK_WHEEL_UP, KeyAction::Up),
+                (VK_WHEEL_UP, KeyAction::Down),
+                (VK_WHEEL_UP, KeyAction::Up),
+            ]
+        );
+        assert_eq!(
+            keys(&mouse.wheel(-WHEEL_DELTA, false, now)),
+            [
+                (VK_WHEEL_DOWN, KeyAction::Down),
+                (VK_WHEEL_DOWN, KeyAction::Up)
+            ]
+        );
+        assert_eq!(
+            keys(&mouse.wheel(-WHEEL_DELTA, true, now)),
+            [
+                (VK_WHEEL_LEFT, KeyAction::Down),
+                (VK_WHEEL_LEFT, KeyAction::Up)
+            ]
+        );
+    }
+
+    #[test]
+    fn adds_up_partial_notches_per_axis() {
+        let mut mouse = MouseKeys::new((4, 4));
+        let now = Instant::now();
+        assert!(mouse.wheel(WHEEL_DELTA / 2, false, now).is_empty());
+        // The horizontal wheel doesn't complete the vertical notch
+        assert!(mouse.wheel(WHEEL_DELTA / 2, true, now).is_empty());
+        assert_eq!(
+            keys(&mouse.wheel(WHEEL_DELTA / 2, false, now)),
+            [(VK_WHEEL_UP, KeyAction::Down), (VK_WHEEL_UP, KeyAction::Up)]
+        );
+        assert_eq!(
+            keys(&mouse.wheel(WHEEL_DELTA / 2, true, now)),
+            [
+                (VK_WHEEL_RIGHT, KeyAction::Down),
+                (VK_WHEEL_RIGHT, KeyAction::Up)
+            ]
+        );
+
+        // Turning back cancels the leftover
+        assert!(mouse.wheel(WHEEL_DELTA / 2, false, now).is_empty());
+        assert!(mouse.wheel(-WHEEL_DELTA / 2, false, now).is_empty());
+        assert!(mouse.wheel(WHEEL_DELTA / 2, false, now).is_empty());
+    }
+
+    #[test]
+    fn drag_replaces_the_button_until_release() {
+        let mut mouse = MouseKeys::new((4, 4));
+        let now = Instant::now();
+        let down = mouse.button_down(0, 0, 0, now);
+        assert_eq!(
+            (down.vk, down.action),
+            (VK_MOUSE_BUTTON[0], KeyAction::Down)
+        );
+        assert!(mouse.moved(4, 4, now).is_empty());
+        assert_eq!(
+            keys(&mouse.moved(0, 5, now)),
+            [(VK_MOUSE_DRAG[0], KeyAction::Replace(VK_MOUSE_BUTTON[0]))]
+        );
+        assert_eq!(mouse.button_up(0, now).vk, VK_MOUSE_DRAG[0]);
+
+        mouse.button_down(0, 0, 0, now);
+        assert_eq!(mouse.button_up(0, now).vk, VK_MOUSE_BUTTON[0]);
+    }
+}
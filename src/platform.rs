@@ -43,6 +43,102 @@ pub fn disable_click_through_windows(window_handle: &raw_window_handle::WindowHa
     }
 }
 
+#[cfg(target_os = "windows")]
+pub fn system_locale_name() -> Option<String> {
+    use windows::Win32::Globalization::GetUserDefaultLocaleName;
+
+    // LOCALE_NAME_MAX_LENGTH
+    let mut buf = [0u16; 85];
+    let len = unsafe { GetUserDefaultLocaleName(&mut buf) };
+    if len > 1 {
+        // Length includes the terminating null
+        Some(String::from_utf16_lossy(&buf[..len as usize - 1]))
+    } else {
+        None
+    }
+}
+
+// POSIX precedence for message catalogs, where an empty variable counts as
+// unset
+#[cfg(not(target_os = "windows"))]
+pub fn system_locale_name() -> Option<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .into_iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty())
+}
+
+/// Seconds allowed between the clicks of a double click.
+#[cfg(target_os = "windows")]
+pub fn double_click_time() -> f32 {
+    use windows::Win32::UI::Input::KeyboardAndMouse::GetDoubleClickTime;
+
+    unsafe { GetDoubleClickTime() as f32 / 1000.0 }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn double_click_time() -> f32 {
+    0.5
+}
+
+/// Held buttons of the XInput controller in slot `user_index`, or None when no
+/// controller is connected there.
+#[cfg(target_os = "windows")]
+pub fn gamepad_state(user_index: u32) -> Option<crate::gamepad::GamepadButtons> {
+    use windows::Win32::Foundation::ERROR_SUCCESS;
+    use windows::Win32::UI::Input::XboxController::{XInputGetState, XINPUT_STATE};
+
+    let mut state = XINPUT_STATE::default();
+    if unsafe { XInputGetState(user_index, &mut state) } != ERROR_SUCCESS.0 {
+        return None;
+    }
+    let pad = state.Gamepad;
+    Some(crate::gamepad::gamepad_buttons(
+        pad.wButtons.0,
+        pad.bLeftTrigger,
+        pad.bRightTrigger,
+    ))
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn gamepad_state(_user_index: u32) -> Option<crate::gamepad::GamepadButtons> {
+    None
+}
+
+/// Stretches the window titled `title` over all monitors and returns the
+/// physical screen position of its top-left corner, or None while the window
+/// doesn't exist yet.
+#[cfg(target_os = "windows")]
+pub fn cover_virtual_screen(title: &str) -> Option<(i32, i32)> {
+    use windows::core::PCWSTR;
+    use windows::Win32::Foundation::RECT;
+    use windows::Win32::UI::WindowsAndMessaging::*;
+
+    let title: Vec<u16> = title.encode_utf16().chain([0]).collect();
+    unsafe {
+        let hwnd = FindWindowW(PCWSTR::null(), PCWSTR(title.as_ptr())).ok()?;
+        // Negative when a monitor is left of or above the primary one
+        let x = GetSystemMetrics(SM_XVIRTUALSCREEN);
+        let y = GetSystemMetrics(SM_YVIRTUALSCREEN);
+        let width = GetSystemMetrics(SM_CXVIRTUALSCREEN);
+        let height = GetSystemMetrics(SM_CYVIRTUALSCREEN);
+
+        // Checked on every call: a DPI change resizes the window, and
+        // monitors can be added or rearranged while it is open
+        let mut rect = RECT::default();
+        GetWindowRect(hwnd, &mut rect).ok()?;
+        if (rect.left, rect.top, rect.right, rect.bottom) != (x, y, x + width, y + height) {
+            let _ = SetWindowPos(hwnd, HWND_TOPMOST, x, y, width, height, SWP_NOACTIVATE);
+        }
+        Some((x, y))
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn cover_virtual_screen(_title: &str) -> Option<(i32, i32)> {
+    None
+}
+
 // use raw_window_handle::{HasWindowHandle, RawWindowHandle};
 // use windows::Win32::UI::WindowsAndMessaging::*;
 // use windows::Win32::Foundation::HWND;
@@ -0,0 +1,78 @@
+// Click ripples: a ring that grows and fades where a mouse button went down.
+//
+// Clicks come from the mouse hook in physical screen pixels, while the
+// overlay they are drawn on lays out in points. The overlay covers the whole
+// virtual screen, whose origin is negative when a monitor sits left of or
+// above the primary one, and a window spanning monitors has a single scale
+// factor. So a click maps to the overlay by subtracting the overlay's origin
+// and dividing by its pixels per point, whatever the DPI of the monitor it
+// happened on.
+
+use std::time::{Duration, Instant};
+
+use eframe::egui;
+
+const RIPPLE_DURATION: Duration = Duration::from_millis(450);
+// Ring radius at the end of the animation, in points
+const RIPPLE_RADIUS: f32 = 28.0;
+const RIPPLE_STROKE: f32 = 3.0;
+
+struct Ripple {
+    pos: (i32, i32),
+    button: usize,
+    start: Instant,
+}
+
+#[derive(Default)]
+pub struct Ripples {
+    ripples: Vec<Ripple>,
+}
+
+impl Ripples {
+    /// Starts a ripple for a press of `button` at physical screen position
+    /// (`x`, `y`).
+    pub fn add(&mut self, button: usize, x: i32, y: i32, time: Instant) {
+        self.ripples.push(Ripple {
+            pos: (x, y),
+            button,
+            start: time,
+        });
+    }
+
+    /// Paints the ripples still running at `now` and drops the finished ones.
+    /// `origin` is the physical screen position of the painter's top-left
+    /// corner, and `colors` holds the left, right and middle button colors.
+    pub fn paint(
+        &mut self,
+        painter: &egui::Painter,
+        origin: (i32, i32),
+        colors: &[egui::Color32; 3],
+        now: Instant,
+    ) {
+        self.ripples
+            .retain(|ripple| now.saturating_duration_since(ripple.start) < RIPPLE_DURATION);
+
+        let pixels_per_point = painter.pixels_per_point();
+        for ripple in &self.ripples {
+            let Some(&color) = colors.get(ripple.button) else {
+                continue;
+            };
+            let t = now.saturating_duration_since(ripple.start).as_secs_f32()
+                / RIPPLE_DURATION.as_secs_f32();
+            // Ease out so the ring starts fast and settles
+            let grown = 1.0 - (1.0 - t) * (1.0 - t);
+            let fade = 1.0 - t;
+
+            let center = egui::pos2(
+                (ripple.pos.0 - origin.0) as f32 / pixels_per_point,
+                (ripple.pos.1 - origin.1) as f32 / pixels_per_point,
+            );
+            painter.circle(
+                center,
+                RIPPLE_RADIUS * (0.25 + 0.75 * grown),
+                color.gamma_multiply(0.3 * fade),
+                egui::Stroke::new(RIPPLE_STROKE, color.gamma_multiply(fade)),
+            );
+        }
+    }
+}
@@ -0,0 +1,747 @@
+use std::time::Duration;
+
+use eframe::egui;
+
+use crate::{
+    animation::{AnimatedElement, Animation, Easing, Edge, Effect},
+    heatmap::{ColorScale, HeatmapRequest, HeatmapSource},
+    keyboard::BuiltinLayout,
+    locale::{tr, Locale},
+    platform,
+    shortcut::{ModifierOrder, Shortcut},
+    skin,
+    theme::{self, ThemePreset},
+};
+
+const DEFAULT_SEQUENCE_PREFIXES: &str = "Ctrl+K";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DisplayMode {
+    /// The latest combo on its own.
+    Combo,
+    /// Running typed text with shortcuts as chips.
+    Ticker,
+}
+
+impl DisplayMode {
+    pub fn label(self) -> &'static str {
+        match self {
+            DisplayMode::Combo => "Combo",
+            DisplayMode::Ticker => "Ticker",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HistoryDirection {
+    NewestOnTop,
+    NewestAtBottom,
+}
+
+impl HistoryDirection {
+    pub fn label(self) -> &'static str {
+        match self {
+            HistoryDirection::NewestOnTop => "Newest on top",
+            HistoryDirection::NewestAtBottom => "Newest at bottom",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyStyle {
+    /// Key names as plain text joined by "+".
+    Text,
+    /// One keycap per key, pressed down while held.
+    Keycaps,
+}
+
+impl KeyStyle {
+    pub fn label(self) -> &'static str {
+        match self {
+            KeyStyle::Text => "Text",
+            KeyStyle::Keycaps => "Keycaps",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyboardLayoutChoice {
+    Builtin(BuiltinLayout),
+    /// The KLE file at `Settings::kle_path`.
+    File,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ThemeChoice {
+    /// The light or dark preset, following the OS.
+    System,
+    Preset(ThemePreset),
+    /// A theme file from `theme::themes_dir`.
+    Custom(String),
+}
+
+/// How long entries stay on screen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Lifetime {
+    /// Visible for the display time after the last press, then fades.
+    Timed,
+    /// Visible until every key is released, then timed from the release.
+    WhileHeld,
+    /// The current entry never fades; only the history does.
+    AlwaysLast,
+}
+
+impl Lifetime {
+    pub fn label(self) -> &'static str {
+        match self {
+            Lifetime::Timed => "Timed",
+            Lifetime::WhileHeld => "Show while held",
+            Lifetime::AlwaysLast => "Always show last",
+        }
+    }
+}
+
+pub struct Settings {
+    pub display_mode: DisplayMode,
+    pub key_style: KeyStyle,
+    /// Skin pack directory name; `None` uses the built-in drawing.
+    pub skin: Option<String>,
+    /// Why the selected skin failed to load, set by the app.
+    pub skin_error: Option<String>,
+    /// Why icon overrides failed to load, set by the app.
+    pub icon_errors: Vec<String>,
+    pub theme: ThemeChoice,
+    /// Custom theme names found in the themes directory.
+    pub themes: Vec<String>,
+    /// Why the custom theme couldn't be loaded, set by the app.
+    pub theme_error: Option<String>,
+    skins: Vec<String>,
+    pub is_show_keyboard: bool,
+    pub keyboard_layout: KeyboardLayoutChoice,
+    pub kle_path: String,
+    /// Why the KLE file failed to load, set by the app.
+    pub keyboard_error: Option<String>,
+    /// Count key presses for the heatmap.
+    pub is_record_heatmap: bool,
+    /// Color the keyboard view by press counts instead of held keys.
+    pub is_show_heatmap: bool,
+    pub color_scale: ColorScale,
+    pub heatmap_source: HeatmapSource,
+    /// Button pressed in the heatmap row, handled by the app.
+    pub heatmap_request: Option<HeatmapRequest>,
+    /// Result of the last heatmap request, set by the app.
+    pub heatmap_status: Option<String>,
+    /// Earlier combos kept on screen below or above the current one.
+    pub history_length: usize,
+    pub history_direction: HistoryDirection,
+    pub lifetime: Lifetime,
+    /// Seconds an entry stays at full opacity.
+    pub display_time: f32,
+    /// Seconds the fade-out takes afterwards.
+    pub fade_time: f32,
+    /// Appear and disappear animation of each element, in
+    /// `AnimatedElement::ALL` order.
+    pub animations: [Animation; 3],
+    /// Element whose animation the settings window is editing.
+    pub animation_element: AnimatedElement,
+    pub is_sided_modifiers: bool,
+    pub modifier_order: ModifierOrder,
+    /// `None` follows the system locale.
+    pub language: Option<Locale>,
+    system_locale: Locale,
+    /// Combos that open a chord sequence such as "Ctrl+K, Ctrl+C".
+    pub sequence_prefixes: Vec<Shortcut>,
+    sequence_prefixes_text: String,
+    sequence_prefix_error: Option<String>,
+    /// Seconds allowed between the strokes of a sequence.
+    pub sequence_timeout: f32,
+    pub is_show_hold: bool,
+    /// Hide plain typing, showing only shortcuts and special keys.
+    pub is_shortcuts_only: bool,
+    /// Merge mouse buttons and wheel into combos such as "Ctrl + Click".
+    pub is_mouse_combos: bool,
+    /// Seconds after which a press counts as a hold rather than a tap.
+    pub hold_threshold: f32,
+    pub is_show_gestures: bool,
+    /// Draw a ripple on screen wherever a mouse button is pressed.
+    pub is_show_ripples: bool,
+    /// Ripple colors for the left, right and middle buttons.
+    pub ripple_colors: [egui::Color32; 3],
+    /// Draw a translucent disc around the pointer.
+    pub is_show_halo: bool,
+    /// Leave a fading trail behind the halo.
+    pub is_show_trail: bool,
+    pub halo_color: egui::Color32,
+    /// Halo radius in points.
+    pub halo_radius: f32,
+    /// Use the system double-click time instead of `double_click_time`.
+    pub is_system_double_click: bool,
+    /// Seconds allowed between the clicks of a double or triple click.
+    pub double_click_time: f32,
+    /// Seconds a mouse button has to be held in place to count as a long-press.
+    pub long_press_time: f32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        let mut settings = Self {
+            display_mode: DisplayMode::Combo,
+            key_style: KeyStyle::Keycaps,
+            skin: None,
+            skin_error: None,
+            icon_errors: Vec::new(),
+            theme: ThemeChoice::System,
+            themes: theme::available_themes(),
+            theme_error: None,
+            skins: skin::available_skins(),
+            is_show_keyboard: false,
+            keyboard_layout: KeyboardLayoutChoice::Builtin(BuiltinLayout::Ansi),
+            kle_path: String::new(),
+            keyboard_error: None,
+            is_record_heatmap: false,
+            is_show_heatmap: false,
+            color_scale: ColorScale::Heat,
+            heatmap_source: HeatmapSource::Session,
+            heatmap_request: None,
+            heatmap_status: None,
+            history_length: 3,
+            history_direction: HistoryDirection::NewestAtBottom,
+            lifetime: Lifetime::Timed,
+            display_time: 2.0,
+            fade_time: 1.0,
+            animations: [Animation::default(); 3],
+            animation_element: AnimatedElement::Combo,
+            is_sided_modifiers: false,
+            modifier_order: ModifierOrder::default(),
+            language: None,
+            system_locale: Locale::system(),
+            sequence_prefixes: Vec::new(),
+            sequence_prefixes_text: DEFAULT_SEQUENCE_PREFIXES.to_string(),
+            sequence_prefix_error: None,
+            sequence_timeout: 2.0,
+            is_show_hold: false,
+            is_mouse_combos: true,
+            is_shortcuts_only: false,
+            hold_threshold: 0.2,
+            is_show_gestures: true,
+            is_show_ripples: false,
+            ripple_colors: [
+                egui::Color32::from_rgb(80, 160, 255),
+                egui::Color32::from_rgb(255, 120, 80),
+                egui::Color32::from_rgb(120, 220, 120),
+            ],
+            is_show_halo: false,
+            is_show_trail: false,
+            halo_color: egui::Color32::from_rgba_unmultiplied(255, 220, 0, 80),
+            halo_radius: 24.0,
+            is_system_double_click: true,
+            double_click_time: platform::double_click_time(),
+            long_press_time: 0.6,
+        };
+        settings.parse_sequence_prefixes();
+        settings
+    }
+}
+
+impl Settings {
+    pub fn locale(&self) -> Locale {
+        self.language.unwrap_or(self.system_locale)
+    }
+
+    pub fn animation(&self, element: AnimatedElement) -> &Animation {
+        &self.animations[element as usize]
+    }
+
+    pub fn multi_click_time(&self) -> Duration {
+        // Read on every use so that changes in the system settings apply
+        if self.is_system_double_click {
+            Duration::from_secs_f32(platform::double_click_time())
+        } else {
+            Duration::from_secs_f32(self.double_click_time)
+        }
+    }
+
+    // Keeps the valid lines, reporting the first one that fails to parse
+    fn parse_sequence_prefixes(&mut self) {
+        self.sequence_prefixes.clear();
+        self.sequence_prefix_error = None;
+
+        for line in self.sequence_prefixes_text.lines() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match Shortcut::parse(line, &self.modifier_order) {
+                Ok(shortcut) => self.sequence_prefixes.push(shortcut),
+                Err(err) => {
+                    if self.sequence_prefix_error.is_none() {
+                        self.sequence_prefix_error = Some(err.to_string());
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub fn settings_ui(ui: &mut egui::Ui, settings: &mut Settings) {
+    let locale = settings.locale();
+
+    egui::Grid::new("settings_grid")
+        .num_columns(2)
+        .spacing([12.0, 8.0])
+        .show(ui, |ui| {
+            ui.label(tr(locale, "Language"));
+            let system_label = format!(
+                "{} ({})",
+                tr(locale, "System"),
+                settings.system_locale.native_name()
+            );
+            egui::ComboBox::from_id_salt("language")
+                .selected_text(match settings.language {
+                    Some(language) => language.native_name().to_string(),
+                    None => system_label.clone(),
+                })
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut settings.language, None, system_label);
+                    for language in Locale::ALL {
+                        ui.selectable_value(
+                            &mut settings.language,
+                            Some(language),
+                            language.native_name(),
+                        );
+                    }
+                });
+            ui.end_row();
+
+            ui.label(tr(locale, "Display"));
+            egui::ComboBox::from_id_salt("display_mode")
+                .selected_text(tr(locale, settings.display_mode.label()))
+                .show_ui(ui, |ui| {
+                    for mode in [DisplayMode::Combo, DisplayMode::Ticker] {
+                        ui.selectable_value(
+                            &mut settings.display_mode,
+                            mode,
+                            tr(locale, mode.label()),
+                        );
+                    }
+                });
+            ui.end_row();
+
+            ui.label(tr(locale, "Key style"));
+            egui::ComboBox::from_id_salt("key_style")
+                .selected_text(tr(locale, settings.key_style.label()))
+                .show_ui(ui, |ui| {
+                    for style in [KeyStyle::Text, KeyStyle::Keycaps] {
+                        ui.selectable_value(
+                            &mut settings.key_style,
+                            style,
+                            tr(locale, style.label()),
+                        );
+                    }
+                });
+            ui.end_row();
+
+            ui.label(tr(locale, "Theme"));
+            ui.vertical(|ui| {
+                let selected = match &settings.theme {
+                    ThemeChoice::System => tr(locale, "System"),
+                    ThemeChoice::Preset(preset) => tr(locale, preset.label()),
+                    ThemeChoice::Custom(name) => name,
+                };
+                let response = egui::ComboBox::from_id_salt("theme")
+                    .selected_text(selected.to_string())
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(
+                            &mut settings.theme,
+                            ThemeChoice::System,
+                            tr(locale, "System"),
+                        );
+                        for preset in ThemePreset::ALL {
+                            ui.selectable_value(
+                                &mut settings.theme,
+                                ThemeChoice::Preset(preset),
+                                tr(locale, preset.label()),
+                            );
+                        }
+                        for name in &settings.themes {
+                            ui.selectable_value(
+                                &mut settings.theme,
+                                ThemeChoice::Custom(name.clone()),
+                                name,
+                            );
+                        }
+                    });
+                // Pick up theme files added while the app is running
+                if response.response.clicked() {
+                    settings.themes = theme::available_themes();
+                }
+                if let Some(err) = &settings.theme_error {
+                    ui.colored_label(ui.visuals().error_fg_color, err);
+                }
+            });
+            ui.end_row();
+
+            ui.label(tr(locale, "Skin"));
+            ui.vertical(|ui| {
+                let selected = settings.skin.as_deref().unwrap_or(tr(locale, "Built-in"));
+                let response = egui::ComboBox::from_id_salt("skin")
+                    .selected_text(selected.to_string())
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut settings.skin, None, tr(locale, "Built-in"));
+                        for name in &settings.skins {
+                            ui.selectable_value(&mut settings.skin, Some(name.clone()), name);
+                        }
+                    });
+                // Pick up packs added while the app is running
+                if response.response.clicked() {
+                    settings.skins = skin::available_skins();
+                }
+                for err in settings.skin_error.iter().chain(&settings.icon_errors) {
+                    ui.colored_label(ui.visuals().error_fg_color, err);
+                }
+            });
+            ui.end_row();
+
+            ui.label(tr(locale, "Keyboard"));
+            ui.checkbox(&mut settings.is_show_keyboard, tr(locale, "Show keyboard"));
+            ui.end_row();
+
+            ui.label(tr(locale, "Keyboard layout"));
+            ui.vertical(|ui| {
+                let selected = match settings.keyboard_layout {
+                    KeyboardLayoutChoice::Builtin(layout) => layout.label(),
+                    KeyboardLayoutChoice::File => tr(locale, "KLE file"),
+                };
+                egui::ComboBox::from_id_salt("keyboard_layout")
+                    .selected_text(selected)
+                    .show_ui(ui, |ui| {
+                        for layout in BuiltinLayout::ALL {
+                            ui.selectable_value(
+                                &mut settings.keyboard_layout,
+                                KeyboardLayoutChoice::Builtin(layout),
+                                layout.label(),
+                            );
+                        }
+                        ui.selectable_value(
+                            &mut settings.keyboard_layout,
+                            KeyboardLayoutChoice::File,
+                            tr(locale, "KLE file"),
+                        );
+                    });
+                if settings.keyboard_layout == KeyboardLayoutChoice::File {
+                    ui.text_edit_singleline(&mut settings.kle_path)
+                        .on_hover_text(tr(
+                            locale,
+                            "Path to a keyboard-layout-editor.com JSON file",
+                        ));
+                    if let Some(err) = &settings.keyboard_error {
+                        ui.colored_label(ui.visuals().error_fg_color, err);
+                    }
+                }
+            });
+            ui.end_row();
+
+            ui.label(tr(locale, "Heatmap"));
+            ui.vertical(|ui| {
+                ui.checkbox(
+                    &mut settings.is_record_heatmap,
+                    tr(locale, "Record key usage"),
+                );
+                ui.checkbox(&mut settings.is_show_heatmap, tr(locale, "Show heatmap"))
+                    .on_hover_text(tr(
+                        locale,
+                        "Color the keyboard by how often each key was pressed",
+                    ));
+            });
+            ui.end_row();
+
+            ui.label(tr(locale, "Color scale"));
+            egui::ComboBox::from_id_salt("color_scale")
+                .selected_text(tr(locale, settings.color_scale.label()))
+                .show_ui(ui, |ui| {
+                    for scale in ColorScale::ALL {
+                        ui.selectable_value(
+                            &mut settings.color_scale,
+                            scale,
+                            tr(locale, scale.label()),
+                        );
+                    }
+                });
+            ui.end_row();
+
+            ui.label(tr(locale, "Heatmap data"));
+            egui::ComboBox::from_id_salt("heatmap_source")
+                .selected_text(tr(locale, settings.heatmap_source.label()))
+                .show_ui(ui, |ui| {
+                    for source in [HeatmapSource::Session, HeatmapSource::AllSessions] {
+                        ui.selectable_value(
+                            &mut settings.heatmap_source,
+                            source,
+                            tr(locale, source.label()),
+                        );
+                    }
+                });
+            ui.end_row();
+
+            ui.label("");
+            ui.vertical(|ui| {
+                ui.horizontal(|ui| {
+                    if ui.button(tr(locale, "Save session")).clicked() {
+                        settings.heatmap_request = Some(HeatmapRequest::SaveSession);
+                    }
+                    if ui.button(tr(locale, "Export PNG")).clicked() {
+                        settings.heatmap_request = Some(HeatmapRequest::ExportPng);
+                    }
+                    if ui.button(tr(locale, "Reset")).clicked() {
+                        settings.heatmap_request = Some(HeatmapRequest::ResetSession);
+                    }
+                });
+                if let Some(status) = &settings.heatmap_status {
+                    ui.label(status);
+                }
+            });
+            ui.end_row();
+
+            ui.label(tr(locale, "Lifetime"));
+            egui::ComboBox::from_id_salt("lifetime")
+                .selected_text(tr(locale, settings.lifetime.label()))
+                .show_ui(ui, |ui| {
+                    for lifetime in [Lifetime::Timed, Lifetime::WhileHeld, Lifetime::AlwaysLast] {
+                        ui.selectable_value(
+                            &mut settings.lifetime,
+                            lifetime,
+                            tr(locale, lifetime.label()),
+                        );
+                    }
+                });
+            ui.end_row();
+
+            ui.label(tr(locale, "Display time"));
+            ui.add(egui::Slider::new(&mut settings.display_time, 0.0..=10.0).suffix(" s"));
+            ui.end_row();
+
+            ui.label(tr(locale, "Fade time"));
+            ui.add(egui::Slider::new(&mut settings.fade_time, 0.0..=5.0).suffix(" s"));
+            ui.end_row();
+
+            ui.label(tr(locale, "Animation"));
+            egui::ComboBox::from_id_salt("animation_element")
+                .selected_text(tr(locale, settings.animation_element.label()))
+                .show_ui(ui, |ui| {
+                    for element in AnimatedElement::ALL {
+                        ui.selectable_value(
+                            &mut settings.animation_element,
+                            element,
+                            tr(locale, element.label()),
+                        );
+                    }
+                });
+            ui.end_row();
+
+            let element = settings.animation_element;
+            let animation = &mut settings.animations[element as usize];
+
+            ui.label(tr(locale, "Appear"));
+            egui::ComboBox::from_id_salt("appear_effect")
+                .selected_text(tr(locale, animation.appear.label()))
+                .show_ui(ui, |ui| {
+                    for effect in Effect::ALL {
+                        ui.selectable_value(
+                            &mut animation.appear,
+                            effect,
+                            tr(locale, effect.label()),
+                        );
+                    }
+                });
+            ui.end_row();
+
+            ui.label(tr(locale, "Disappear"));
+            egui::ComboBox::from_id_salt("disappear_effect")
+                .selected_text(tr(locale, animation.disappear.label()))
+                .show_ui(ui, |ui| {
+                    for effect in Effect::ALL {
+                        ui.selectable_value(
+                            &mut animation.disappear,
+                            effect,
+                            tr(locale, effect.label()),
+                        );
+                    }
+                });
+            ui.end_row();
+
+            ui.label(tr(locale, "Slide edge"));
+            egui::ComboBox::from_id_salt("slide_edge")
+                .selected_text(tr(locale, animation.edge.label()))
+                .show_ui(ui, |ui| {
+                    for edge in Edge::ALL {
+                        ui.selectable_value(&mut animation.edge, edge, tr(locale, edge.label()));
+                    }
+                });
+            ui.end_row();
+
+            ui.label(tr(locale, "Easing"));
+            egui::ComboBox::from_id_salt("easing")
+                .selected_text(tr(locale, animation.easing.label()))
+                .show_ui(ui, |ui| {
+                    for easing in Easing::ALL {
+                        ui.selectable_value(
+                            &mut animation.easing,
+                            easing,
+                            tr(locale, easing.label()),
+                        );
+                    }
+                });
+            ui.end_row();
+
+            ui.label(tr(locale, "Animation time"));
+            ui.add(egui::Slider::new(&mut animation.duration, 0.0..=1.0).suffix(" s"));
+            ui.end_row();
+
+            // Only combos count repeated presses
+            ui.label("");
+            ui.add_enabled(
+                element == AnimatedElement::Combo,
+                egui::Checkbox::new(&mut animation.is_bounce, tr(locale, "Bounce on repeat")),
+            );
+            ui.end_row();
+
+            ui.label(tr(locale, "History"));
+            ui.add(egui::Slider::new(&mut settings.history_length, 0..=10));
+            ui.end_row();
+
+            ui.label(tr(locale, "History order"));
+            egui::ComboBox::from_id_salt("history_direction")
+                .selected_text(tr(locale, settings.history_direction.label()))
+                .show_ui(ui, |ui| {
+                    for direction in [
+                        HistoryDirection::NewestOnTop,
+                        HistoryDirection::NewestAtBottom,
+                    ] {
+                        ui.selectable_value(
+                            &mut settings.history_direction,
+                            direction,
+                            tr(locale, direction.label()),
+                        );
+                    }
+                });
+            ui.end_row();
+
+            ui.label(tr(locale, "Privacy"));
+            ui.checkbox(
+                &mut settings.is_shortcuts_only,
+                tr(locale, "Shortcuts only (hide typing)"),
+            );
+            ui.end_row();
+
+            ui.label(tr(locale, "Modifiers"));
+            ui.checkbox(
+                &mut settings.is_sided_modifiers,
+                tr(locale, "Distinguish left/right"),
+            );
+            ui.end_row();
+
+            ui.label(tr(locale, "Modifier order"));
+            egui::ComboBox::from_id_salt("modifier_order")
+                .selected_text(settings.modifier_order.label())
+                .show_ui(ui, |ui| {
+                    for order in ModifierOrder::PRESETS {
+                        ui.selectable_value(&mut settings.modifier_order, order, order.label());
+                    }
+                });
+            ui.end_row();
+
+            ui.label(tr(locale, "Mouse"));
+            ui.checkbox(
+                &mut settings.is_mouse_combos,
+                tr(locale, "Include in combos"),
+            );
+            ui.end_row();
+
+            ui.label(tr(locale, "Sequence prefixes"));
+            ui.vertical(|ui| {
+                let response = ui
+                    .text_edit_multiline(&mut settings.sequence_prefixes_text)
+                    .on_hover_text(tr(locale, "One shortcut per line, e.g. Ctrl+K or Ctrl+X"));
+                if response.changed() {
+                    settings.parse_sequence_prefixes();
+                }
+                if let Some(err) = &settings.sequence_prefix_error {
+                    ui.colored_label(ui.visuals().error_fg_color, err);
+                }
+            });
+            ui.end_row();
+
+            ui.label(tr(locale, "Sequence timeout"));
+            ui.add(egui::Slider::new(&mut settings.sequence_timeout, 0.2..=5.0).suffix(" s"));
+            ui.end_row();
+
+            ui.label(tr(locale, "Hold duration"));
+            ui.checkbox(&mut settings.is_show_hold, tr(locale, "Show tap/hold"));
+            ui.end_row();
+
+            ui.label(tr(locale, "Hold threshold"));
+            ui.add(egui::Slider::new(&mut settings.hold_threshold, 0.05..=1.0).suffix(" s"));
+            ui.end_row();
+
+            ui.label(tr(locale, "Gestures"));
+            ui.checkbox(
+                &mut settings.is_show_gestures,
+                tr(locale, "Show mouse gestures"),
+            );
+            ui.end_row();
+
+            ui.label(tr(locale, "Click ripples"));
+            ui.vertical(|ui| {
+                ui.checkbox(
+                    &mut settings.is_show_ripples,
+                    tr(locale, "Show click ripples"),
+                );
+                ui.horizontal(|ui| {
+                    for (color, label) in settings.ripple_colors.iter_mut().zip([
+                        "Click",
+                        "Right Click",
+                        "Middle Click",
+                    ]) {
+                        ui.color_edit_button_srgba(color);
+                        ui.label(tr(locale, label));
+                    }
+                });
+            });
+            ui.end_row();
+
+            ui.label(tr(locale, "Cursor halo"));
+            ui.vertical(|ui| {
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut settings.is_show_halo, tr(locale, "Show cursor halo"));
+                    ui.color_edit_button_srgba(&mut settings.halo_color);
+                });
+                ui.add_enabled(
+                    settings.is_show_halo,
+                    egui::Checkbox::new(&mut settings.is_show_trail, tr(locale, "Motion trail")),
+                );
+                ui.add_enabled(
+                    settings.is_show_halo,
+                    egui::Slider::new(&mut settings.halo_radius, 8.0..=64.0).suffix(" pt"),
+                );
+            });
+            ui.end_row();
+
+            ui.label(tr(locale, "Double-click time"));
+            ui.horizontal(|ui| {
+                if ui
+                    .checkbox(&mut settings.is_system_double_click, tr(locale, "System"))
+                    .changed()
+                {
+                    settings.double_click_time = platform::double_click_time();
+                }
+                ui.add_enabled(
+                    !settings.is_system_double_click,
+                    egui::Slider::new(&mut settings.double_click_time, 0.1..=1.5).suffix(" s"),
+                );
+            });
+            ui.end_row();
+
+            ui.label(tr(locale, "Long-press time"));
+            ui.add(egui::Slider::new(&mut settings.long_press_time, 0.2..=2.0).suffix(" s"));
+            ui.end_row();
+        });
+}
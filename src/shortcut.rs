@@ -0,0 +1,528 @@
+// Canonical keyboard shortcuts built on top of virtual-key codes.
+//
+// A `Shortcut` is a set of modifiers plus the remaining keys in press order.
+// Parsing and formatting use layout-independent names (US punctuation for OEM
+// keys) so that strings in settings files round-trip on any keyboard layout.
+
+use std::fmt;
+
+use indexmap::IndexSet;
+
+use crate::key_names;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Modifier {
+    Ctrl,
+    Shift,
+    Alt,
+    Win,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Side {
+    Any,
+    Left,
+    Right,
+}
+
+impl Modifier {
+    pub fn name(self) -> &'static str {
+        match self {
+            Modifier::Ctrl => "Ctrl",
+            Modifier::Shift => "Shift",
+            Modifier::Alt => "Alt",
+            Modifier::Win => "Win",
+        }
+    }
+
+    /// Splits a virtual key into modifier and side. The generic `VK_SHIFT`,
+    /// `VK_CONTROL` and `VK_MENU` codes map to `Side::Any`.
+    pub fn from_vk(vk: u32) -> Option<(Modifier, Side)> {
+        let modifier = match vk {
+            0x10 => (Modifier::Shift, Side::Any),
+            0x11 => (Modifier::Ctrl, Side::Any),
+            0x12 => (Modifier::Alt, Side::Any),
+            0xA0 => (Modifier::Shift, Side::Left),
+            0xA1 => (Modifier::Shift, Side::Right),
+            0xA2 => (Modifier::Ctrl, Side::Left),
+            0xA3 => (Modifier::Ctrl, Side::Right),
+            0xA4 => (Modifier::Alt, Side::Left),
+            0xA5 => (Modifier::Alt, Side::Right),
+            0x5B => (Modifier::Win, Side::Left),
+            0x5C => (Modifier::Win, Side::Right),
+            key_names::VK_ALTGR_CTRL => (Modifier::Ctrl, Side::Left),
+            _ => return None,
+        };
+        Some(modifier)
+    }
+
+    pub fn to_vk(self, side: Side) -> u32 {
+        match (self, side) {
+            (Modifier::Shift, Side::Any) => 0x10,
+            (Modifier::Ctrl, Side::Any) => 0x11,
+            (Modifier::Alt, Side::Any) => 0x12,
+            (Modifier::Shift, Side::Left) => 0xA0,
+            (Modifier::Shift, Side::Right) => 0xA1,
+            (Modifier::Ctrl, Side::Left) => 0xA2,
+            (Modifier::Ctrl, Side::Right) => 0xA3,
+            (Modifier::Alt, Side::Left) => 0xA4,
+            (Modifier::Alt, Side::Right) => 0xA5,
+            // There is no generic Win key code
+            (Modifier::Win, Side::Any | Side::Left) => 0x5B,
+            (Modifier::Win, Side::Right) => 0x5C,
+        }
+    }
+
+    fn sided_name(self, side: Side) -> String {
+        match side {
+            Side::Any => self.name().to_string(),
+            Side::Left => format!("L{}", self.name()),
+            Side::Right => format!("R{}", self.name()),
+        }
+    }
+}
+
+/// Order in which modifiers are listed in front of the other keys.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ModifierOrder(pub [Modifier; 4]);
+
+impl Default for ModifierOrder {
+    fn default() -> Self {
+        Self::PRESETS[0]
+    }
+}
+
+impl ModifierOrder {
+    /// Selectable orders: Windows convention, macOS convention (⌃⌥⇧⌘) and
+    /// platform key first.
+    pub const PRESETS: [ModifierOrder; 3] = [
+        ModifierOrder([
+            Modifier::Ctrl,
+            Modifier::Shift,
+            Modifier::Alt,
+            Modifier::Win,
+        ]),
+        ModifierOrder([
+            Modifier::Ctrl,
+            Modifier::Alt,
+            Modifier::Shift,
+            Modifier::Win,
+        ]),
+        ModifierOrder([
+            Modifier::Win,
+            Modifier::Ctrl,
+            Modifier::Alt,
+            Modifier::Shift,
+        ]),
+    ];
+
+    pub fn label(&self) -> String {
+        self.0.map(Modifier::name).join(" + ")
+    }
+
+    fn position(&self, modifier: Modifier) -> usize {
+        self.0.iter().position(|&m| m == modifier).unwrap_or(0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseShortcutError {
+    Empty,
+    UnknownKey(String),
+}
+
+impl fmt::Display for ParseShortcutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseShortcutError::Empty => write!(f, "empty shortcut"),
+            ParseShortcutError::UnknownKey(name) => write!(f, "unknown key name \"{name}\""),
+        }
+    }
+}
+
+impl std::error::Error for ParseShortcutError {}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Shortcut {
+    modifiers: Vec<(Modifier, Side)>,
+    keys: Vec<u32>,
+}
+
+impl Shortcut {
+    /// Builds a canonical shortcut from pressed virtual keys.
+    pub fn from_keys<'a>(keys: impl IntoIterator<Item = &'a u32>, order: &ModifierOrder) -> Self {
+        let mut modifiers = Vec::new();
+        let mut rest = Vec::new();
+        for &vk in keys {
+            match Modifier::from_vk(vk) {
+                Some(modifier) => modifiers.push(modifier),
+                None => {
+                    if !rest.contains(&vk) {
+                        rest.push(vk);
+                    }
+                }
+            }
+        }
+        Self::canonical(modifiers, rest, order)
+    }
+
+    fn canonical(
+        mut modifiers: Vec<(Modifier, Side)>,
+        keys: Vec<u32>,
+        order: &ModifierOrder,
+    ) -> Self {
+        modifiers.sort_by_key(|&(modifier, side)| (order.position(modifier), side));
+        modifiers.dedup();
+        Self { modifiers, keys }
+    }
+
+    /// Parses strings such as `"Ctrl+Shift+P"`, `"LAlt + F4"` or `"Ctrl++"`.
+    /// Names are matched case-insensitively.
+    pub fn parse(s: &str, order: &ModifierOrder) -> Result<Self, ParseShortcutError> {
+        let mut modifiers = Vec::new();
+        let mut keys = Vec::new();
+
+        for token in tokenize(s) {
+            if let Some(modifier) = parse_modifier(&token) {
+                modifiers.push(modifier);
+            } else if let Some(vk) = parse_key(&token) {
+                if !keys.contains(&vk) {
+                    keys.push(vk);
+                }
+            } else {
+                return Err(ParseShortcutError::UnknownKey(token));
+            }
+        }
+
+        if modifiers.is_empty() && keys.is_empty() {
+            return Err(ParseShortcutError::Empty);
+        }
+        Ok(Self::canonical(modifiers, keys, order))
+    }
+
+    pub fn modifiers(&self) -> &[(Modifier, Side)] {
+        &self.modifiers
+    }
+
+    pub fn keys(&self) -> &[u32] {
+        &self.keys
+    }
+
+    /// Index of `vk` in the canonical order, for sorting the keys the shortcut
+    /// was built from without losing their exact codes (such as the AltGr
+    /// Ctrl). Keys that aren't part of the shortcut sort last.
+    pub fn position(&self, vk: u32) -> usize {
+        let position = match Modifier::from_vk(vk) {
+            Some(modifier) => self.modifiers.iter().position(|&m| m == modifier),
+            None => self
+                .keys
+                .iter()
+                .position(|&key| key == vk)
+                .map(|i| self.modifiers.len() + i),
+        };
+        position.unwrap_or(usize::MAX)
+    }
+
+    pub fn has_modifier(&self, modifier: Modifier) -> bool {
+        self.modifiers.iter().any(|&(m, _)| m == modifier)
+    }
+
+    /// Virtual keys of the shortcut, modifiers first.
+    pub fn to_vks(&self) -> IndexSet<u32> {
+        self.modifiers
+            .iter()
+            .map(|&(modifier, side)| modifier.to_vk(side))
+            .chain(self.keys.iter().copied())
+            .collect()
+    }
+
+    /// Compares two shortcuts as key sets. A modifier with `Side::Any` matches
+    /// either side; with `ignore_side` set, left and right also match each other.
+    pub fn matches(&self, other: &Shortcut, ignore_side: bool) -> bool {
+        let side_matches =
+            |a: Side, b: Side| ignore_side || a == b || a == Side::Any || b == Side::Any;
+        let covers = |x: &Shortcut, y: &Shortcut| {
+            x.modifiers.iter().all(|&(m, s)| {
+                y.modifiers
+                    .iter()
+                    .any(|&(m2, s2)| m == m2 && side_matches(s, s2))
+            })
+        };
+
+        let mut keys = self.keys.clone();
+        let mut other_keys = other.keys.clone();
+        keys.sort_unstable();
+        other_keys.sort_unstable();
+
+        keys == other_keys && covers(self, other) && covers(other, self)
+    }
+
+    /// Formats with a custom separator, e.g. `" + "` for the overlay.
+    pub fn format(&self, separator: &str) -> String {
+        self.modifiers
+            .iter()
+            .map(|&(modifier, side)| modifier.sided_name(side))
+            .chain(self.keys.iter().map(|&vk| key_name(vk)))
+            .collect::<Vec<_>>()
+            .join(separator)
+    }
+}
+
+impl fmt::Display for Shortcut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.format("+"))
+    }
+}
+
+/// Keys that produce text on their own: letters, digits, punctuation, the
+/// numpad and the keys used while typing (Space, Enter, Tab, Backspace).
+pub fn is_typing_key(vk: u32) -> bool {
+    matches!(
+        vk,
+        0x08 | 0x09 | 0x0D | 0x20
+            | 0x30..=0x39
+            | 0x41..=0x5A
+            | 0x60..=0x6F
+            | 0xBA..=0xC0
+            | 0xDB..=0xDF
+            | 0xE2
+            | key_names::VK_NUMPAD_ENTER
+    )
+}
+
+/// Whether a combo is plain typing rather than a shortcut: only typing keys,
+/// optionally with Shift or AltGr (which also just select characters).
+pub fn is_text_input(keys: &IndexSet<u32>) -> bool {
+    let is_altgr = keys.contains(&key_names::VK_ALTGR_CTRL);
+
+    keys.iter().all(|&vk| match Modifier::from_vk(vk) {
+        Some((Modifier::Shift, _)) => true,
+        Some((Modifier::Alt, Side::Right)) => is_altgr,
+        Some(_) => vk == key_names::VK_ALTGR_CTRL,
+        None => is_typing_key(vk),
+    })
+}
+
+// Punctuation on the US layout, used for layout-independent OEM key names
+const US_OEM_KEYS: &[(u32, &str)] = &[
+    (0xBA, ";"),
+    (0xBB, "="),
+    (0xBC, ","),
+    (0xBD, "-"),
+    (0xBE, "."),
+    (0xBF, "/"),
+    (0xC0, "`"),
+    (0xDB, "["),
+    (0xDC, "\\"),
+    (0xDD, "]"),
+    (0xDE, "'"),
+];
+
+const KEY_ALIASES: &[(&str, u32)] = &[
+    ("+", 0xBB),
+    ("plus", 0xBB),
+    ("escape", 0x1B),
+    ("return", 0x0D),
+    ("del", 0x2E),
+    ("ins", 0x2D),
+    ("pgup", 0x21),
+    ("pgdn", 0x22),
+    ("left", 0x25),
+    ("up", 0x26),
+    ("right", 0x27),
+    ("down", 0x28),
+    ("menu", 0x5D),
+    ("prtsc", 0x2C),
+];
+
+/// Layout-independent name of a non-modifier virtual key.
+pub fn key_name(vk: u32) -> String {
+    if (0x30..=0x39).contains(&vk) || (0x41..=0x5A).contains(&vk) {
+        return char::from_u32(vk).map(String::from).unwrap_or_default();
+    }
+    if let Some(&(_, name)) = US_OEM_KEYS.iter().find(|&&(code, _)| code == vk) {
+        return name.to_string();
+    }
+    if let Some(name) = key_names::vk_name(vk).or_else(|| key_names::oem_name(vk)) {
+        return name.to_string();
+    }
+    format!("VK_{vk:02X}")
+}
+
+fn parse_modifier(token: &str) -> Option<(Modifier, Side)> {
+    let lower = token.to_ascii_lowercase();
+    let (side, base) = if let Some(base) = lower.strip_prefix('l').filter(|b| is_modifier_name(b)) {
+        (Side::Left, base)
+    } else if let Some(base) = lower.strip_prefix('r').filter(|b| is_modifier_name(b)) {
+        (Side::Right, base)
+    } else if lower == "altgr" {
+        return Some((Modifier::Alt, Side::Right));
+    } else {
+        (Side::Any, lower.as_str())
+    };
+
+    let modifier = match base {
+        "ctrl" | "control" => Modifier::Ctrl,
+        "shift" => Modifier::Shift,
+        "alt" | "option" => Modifier::Alt,
+        "win" | "super" | "meta" | "cmd" => Modifier::Win,
+        _ => return None,
+    };
+    Some((modifier, side))
+}
+
+fn is_modifier_name(name: &str) -> bool {
+    matches!(
+        name,
+        "ctrl" | "control" | "shift" | "alt" | "option" | "win" | "super" | "meta" | "cmd"
+    )
+}
+
+fn parse_key(token: &str) -> Option<u32> {
+    if token.chars().count() == 1 {
+        let c = token.chars().next()?.to_ascii_uppercase();
+        if c.is_ascii_digit() || c.is_ascii_uppercase() {
+            return Some(c as u32);
+        }
+    }
+    if let Some(&(vk, _)) = US_OEM_KEYS.iter().find(|&&(_, name)| name == token) {
+        return Some(vk);
+    }
+    if let Some(&(_, vk)) = KEY_ALIASES
+        .iter()
+        .find(|&&(alias, _)| alias.eq_ignore_ascii_case(token))
+    {
+        return Some(vk);
+    }
+    if let Some(hex) = token.strip_prefix("VK_") {
+        if let Ok(vk) = u32::from_str_radix(hex, 16) {
+            return Some(vk);
+        }
+    }
+    // Names are unique across the key space, so the first match is the key
+    (0..=0x1FF).find(|&vk| {
+        Modifier::from_vk(vk).is_none()
+            && key_names::vk_name(vk)
+                .or_else(|| key_names::oem_name(vk))
+                .is_some_and(|name| name.eq_ignore_ascii_case(token))
+    })
+}
+
+// Splits on '+', treating a '+' that starts a token (as in "Ctrl++" or
+// "Num +") as part of the key name.
+fn tokenize(s: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+
+    for c in s.chars() {
+        let trimmed = current.trim();
+        if c == '+' && !trimmed.is_empty() && !trimmed.eq_ignore_ascii_case("num") {
+            tokens.push(trimmed.to_string());
+            current.clear();
+        } else {
+            current.push(c);
+        }
+    }
+    let trimmed = current.trim();
+    if !trimmed.is_empty() {
+        tokens.push(trimmed.to_string());
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Shortcut {
+        Shortcut::parse(s, &ModifierOrder::default()).unwrap()
+    }
+
+    #[test]
+    fn formats_canonically() {
+        let cases = [
+            ("ctrl+shift+p", "Ctrl+Shift+P"),
+            ("Shift + Ctrl + P", "Ctrl+Shift+P"),
+            ("LAlt + F4", "LAlt+F4"),
+            ("AltGr+E", "RAlt+E"),
+            ("Ctrl++", "Ctrl+="),
+            ("Ctrl+Num +", "Ctrl+Num +"),
+            ("cmd+escape", "Win+Esc"),
+            ("Win+Num Enter", "Win+Num Enter"),
+            ("Ctrl+K+K", "Ctrl+K"),
+            ("Ctrl+VK_FF", "Ctrl+VK_FF"),
+        ];
+        for (input, formatted) in cases {
+            let shortcut = parse(input);
+            assert_eq!(shortcut.to_string(), formatted, "{input}");
+            assert_eq!(parse(formatted), shortcut, "{formatted}");
+        }
+    }
+
+    #[test]
+    fn every_key_name_round_trips() {
+        for vk in (0..=0x1FF).filter(|&vk| Modifier::from_vk(vk).is_none()) {
+            let name = key_name(vk);
+            let shortcut = Shortcut::parse(&name, &ModifierOrder::default());
+            assert_eq!(
+                shortcut.as_ref().map(Shortcut::keys),
+                Ok(&[vk][..]),
+                "VK {vk:#X} named \"{name}\""
+            );
+        }
+    }
+
+    #[test]
+    fn every_modifier_round_trips() {
+        for vk in [
+            0x10, 0x11, 0x12, 0xA0, 0xA1, 0xA2, 0xA3, 0xA4, 0xA5, 0x5B, 0x5C,
+        ] {
+            let shortcut = Shortcut::from_keys(&[vk, 0x41], &ModifierOrder::default());
+            assert_eq!(parse(&shortcut.to_string()), shortcut, "VK {vk:#X}");
+        }
+    }
+
+    #[test]
+    fn orders_modifiers() {
+        let keys = [0x41, 0x5B, 0x10, 0xA4, 0xA3];
+        let formatted = [
+            "RCtrl+Shift+LAlt+LWin+A",
+            "RCtrl+LAlt+Shift+LWin+A",
+            "LWin+RCtrl+LAlt+Shift+A",
+        ];
+        for (order, formatted) in ModifierOrder::PRESETS.iter().zip(formatted) {
+            let shortcut = Shortcut::from_keys(&keys, order);
+            assert_eq!(shortcut.to_string(), formatted);
+            assert_eq!(Shortcut::parse(formatted, order), Ok(shortcut));
+        }
+    }
+
+    #[test]
+    fn sorts_raw_keys_by_position() {
+        let keys = [0x41, key_names::VK_ALTGR_CTRL, 0xA5, 0xA0];
+        let shortcut = Shortcut::from_keys(&keys, &ModifierOrder::default());
+        let mut sorted = keys;
+        sorted.sort_by_key(|&vk| shortcut.position(vk));
+        assert_eq!(sorted, [key_names::VK_ALTGR_CTRL, 0xA0, 0xA5, 0x41]);
+        assert_eq!(shortcut.position(0x42), usize::MAX);
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        let order = ModifierOrder::default();
+        assert_eq!(Shortcut::parse(" ", &order), Err(ParseShortcutError::Empty));
+        assert_eq!(
+            Shortcut::parse("Ctrl+Foo", &order),
+            Err(ParseShortcutError::UnknownKey("Foo".to_string()))
+        );
+    }
+
+    #[test]
+    fn matches_across_sides() {
+        let left = parse("LCtrl+C");
+        assert!(left.matches(&parse("Ctrl+C"), false));
+        assert!(!left.matches(&parse("RCtrl+C"), false));
+        assert!(left.matches(&parse("RCtrl+C"), true));
+        assert!(!left.matches(&parse("Ctrl+Shift+C"), true));
+    }
+}
@@ -0,0 +1,344 @@
+// Skin packs: a directory under `skins/` next to the executable holding a
+// `skin.toml` manifest and PNG images. Any section may be left out, in which
+// case that part keeps the built-in vector drawing.
+//
+//     name = "Brand"
+//
+//     [keycap]
+//     normal = { image = "key.png", slice = [12, 12, 12, 12] }
+//     pressed = { image = "key_pressed.png", slice = [12, 12, 12, 12] }
+//     text_color = "#ffffff"
+//     padding = 14.0
+//
+//     [mouse]
+//     body = "mouse.png"
+//     left = "mouse_left.png"
+//     right = "mouse_right.png"
+//
+//     [background]
+//     image = "background.png"
+//     slice = [8, 8, 8, 8]
+//
+// An image is either a plain path or a table with a nine-slice border given
+// as left, top, right and bottom margins in image pixels; the corners keep
+// their size and the edges and center stretch. Mouse button images are drawn
+// over the body while the button is held and must share its size.
+
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use eframe::egui;
+use serde::Deserialize;
+
+use crate::{mouse, theme::TextStyle};
+
+const MANIFEST: &str = "skin.toml";
+
+#[derive(Debug)]
+pub enum SkinError {
+    Io(PathBuf, io::Error),
+    Manifest(toml::de::Error),
+    Image(PathBuf, image::ImageError),
+    Color(String),
+}
+
+impl fmt::Display for SkinError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SkinError::Io(path, err) => write!(f, "cannot read {}: {err}", path.display()),
+            SkinError::Manifest(err) => write!(f, "invalid {MANIFEST}: {err}"),
+            SkinError::Image(path, err) => write!(f, "cannot load {}: {err}", path.display()),
+            SkinError::Color(text) => write!(f, "invalid color \"{text}\""),
+        }
+    }
+}
+
+impl std::error::Error for SkinError {}
+
+#[derive(Deserialize)]
+struct Manifest {
+    name: Option<String>,
+    keycap: Option<KeycapManifest>,
+    mouse: Option<MouseManifest>,
+    background: Option<ImageManifest>,
+}
+
+#[derive(Deserialize)]
+struct KeycapManifest {
+    normal: ImageManifest,
+    pressed: Option<ImageManifest>,
+    text_color: Option<String>,
+    padding: Option<f32>,
+}
+
+#[derive(Deserialize)]
+struct MouseManifest {
+    body: ImageManifest,
+    left: Option<ImageManifest>,
+    right: Option<ImageManifest>,
+    middle: Option<ImageManifest>,
+    x1: Option<ImageManifest>,
+    x2: Option<ImageManifest>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ImageManifest {
+    Path(PathBuf),
+    Table {
+        image: PathBuf,
+        slice: Option<[u32; 4]>,
+    },
+}
+
+struct SkinImage {
+    texture: egui::TextureHandle,
+    // Nine-slice margins: left, top, right, bottom
+    slice: Option<[f32; 4]>,
+}
+
+impl SkinImage {
+    fn load(ctx: &egui::Context, dir: &Path, manifest: &ImageManifest) -> Result<Self, SkinError> {
+        let (file, slice) = match manifest {
+            ImageManifest::Path(file) => (file, None),
+            ImageManifest::Table { image, slice } => {
+                (image, slice.map(|slice| slice.map(|px| px as f32)))
+            }
+        };
+        let path = dir.join(file);
+        let bytes = fs::read(&path).map_err(|err| SkinError::Io(path.clone(), err))?;
+        let rgba = image::load_from_memory(&bytes)
+            .map_err(|err| SkinError::Image(path.clone(), err))?
+            .to_rgba8();
+        let size = [rgba.width() as usize, rgba.height() as usize];
+        let image = egui::ColorImage::from_rgba_unmultiplied(size, rgba.as_raw());
+        let texture = ctx.load_texture(path.to_string_lossy(), image, Default::default());
+        Ok(Self { texture, slice })
+    }
+
+    fn size(&self) -> egui::Vec2 {
+        self.texture.size_vec2()
+    }
+
+    fn paint(&self, painter: &egui::Painter, rect: egui::Rect, tint: egui::Color32) {
+        let full_uv = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+        let Some([left, top, right, bottom]) = self.slice else {
+            painter.image(self.texture.id(), rect, full_uv, tint);
+            return;
+        };
+
+        // Shrink the margins evenly when the target is smaller than the border
+        let scale = (rect.width() / (left + right))
+            .min(rect.height() / (top + bottom))
+            .min(1.0);
+        let size = self.size();
+        let xs = [
+            rect.left(),
+            rect.left() + left * scale,
+            rect.right() - right * scale,
+            rect.right(),
+        ];
+        let ys = [
+            rect.top(),
+            rect.top() + top * scale,
+            rect.bottom() - bottom * scale,
+            rect.bottom(),
+        ];
+        let us = [0.0, left / size.x, 1.0 - right / size.x, 1.0];
+        let vs = [0.0, top / size.y, 1.0 - bottom / size.y, 1.0];
+
+        let mut mesh = egui::Mesh::with_texture(self.texture.id());
+        for row in 0..4 {
+            for col in 0..4 {
+                mesh.vertices.push(egui::epaint::Vertex {
+                    pos: egui::pos2(xs[col], ys[row]),
+                    uv: egui::pos2(us[col], vs[row]),
+                    color: tint,
+                });
+            }
+        }
+        for row in 0..3 {
+            for col in 0..3 {
+                let i = (row * 4 + col) as u32;
+                mesh.add_triangle(i, i + 1, i + 5);
+                mesh.add_triangle(i, i + 5, i + 4);
+            }
+        }
+        painter.add(egui::Shape::mesh(mesh));
+    }
+}
+
+struct KeycapSkin {
+    normal: SkinImage,
+    pressed: Option<SkinImage>,
+    text_color: Option<egui::Color32>,
+    // Space left and right of the label
+    padding: f32,
+}
+
+struct MouseSkin {
+    body: SkinImage,
+    // Indexed like the mouse button state: left, right, middle, X1, X2
+    buttons: [Option<SkinImage>; 5],
+}
+
+pub struct Skin {
+    pub name: String,
+    keycap: Option<KeycapSkin>,
+    mouse: Option<MouseSkin>,
+    background: Option<SkinImage>,
+}
+
+/// Directory holding the skin packs, next to the executable.
+pub fn skins_dir() -> Option<PathBuf> {
+    Some(std::env::current_exe().ok()?.parent()?.join("skins"))
+}
+
+/// Names of the skin packs found in `skins_dir`, sorted.
+pub fn available_skins() -> Vec<String> {
+    let Some(entries) = skins_dir().and_then(|dir| fs::read_dir(dir).ok()) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().join(MANIFEST).is_file())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect();
+    names.sort();
+    names
+}
+
+impl Skin {
+    /// Loads the skin pack `name` from `skins_dir`.
+    pub fn load(ctx: &egui::Context, name: &str) -> Result<Self, SkinError> {
+        let dir = skins_dir().unwrap_or_default().join(name);
+        let manifest_path = dir.join(MANIFEST);
+        let text =
+            fs::read_to_string(&manifest_path).map_err(|err| SkinError::Io(manifest_path, err))?;
+        let manifest: Manifest = toml::from_str(&text).map_err(SkinError::Manifest)?;
+
+        let load = |image: &ImageManifest| SkinImage::load(ctx, &dir, image);
+        let load_optional = |image: &Option<ImageManifest>| image.as_ref().map(load).transpose();
+
+        let keycap = match &manifest.keycap {
+            Some(keycap) => Some(KeycapSkin {
+                normal: load(&keycap.normal)?,
+                pressed: load_optional(&keycap.pressed)?,
+                text_color: keycap
+                    .text_color
+                    .as_deref()
+                    .map(|hex| {
+                        egui::Color32::from_hex(hex).map_err(|_| SkinError::Color(hex.to_string()))
+                    })
+                    .transpose()?,
+                padding: keycap.padding.unwrap_or(12.0),
+            }),
+            None => None,
+        };
+        let mouse = match &manifest.mouse {
+            Some(mouse) => Some(MouseSkin {
+                body: load(&mouse.body)?,
+                buttons: [
+                    load_optional(&mouse.left)?,
+                    load_optional(&mouse.right)?,
+                    load_optional(&mouse.middle)?,
+                    load_optional(&mouse.x1)?,
+                    load_optional(&mouse.x2)?,
+                ],
+            }),
+            None => None,
+        };
+        let background = load_optional(&manifest.background)?;
+
+        Ok(Self {
+            name: manifest.name.unwrap_or_else(|| name.to_string()),
+            keycap,
+            mouse,
+            background,
+        })
+    }
+
+    pub fn has_keycaps(&self) -> bool {
+        self.keycap.is_some()
+    }
+
+    /// Skinned counterpart of `keycap::draw_keycap`; `None` when the skin has
+    /// no keycap images. The label takes the skin's text color if it sets
+    /// one, or else the color of `style`.
+    pub fn draw_keycap(
+        &self,
+        ui: &egui::Ui,
+        pos: egui::Pos2,
+        label: &str,
+        style: &TextStyle,
+        is_pressed: bool,
+        alpha: u8,
+    ) -> Option<egui::Vec2> {
+        let keycap = self.keycap.as_ref()?;
+        let tint = egui::Color32::from_white_alpha(alpha);
+        let text_color = keycap.text_color.map_or(style.color, |color| tint * color);
+        let font_size = style.font.size;
+        let galley = ui.painter().layout_no_wrap(
+            label.to_string(),
+            egui::FontId::new(font_size * 0.5, style.font.family.clone()),
+            text_color,
+        );
+
+        let height = crate::keycap::cap_height(font_size);
+        let width = (galley.size().x + keycap.padding * 2.0).max(height);
+        let rect = egui::Rect::from_min_size(pos, egui::vec2(width, height));
+
+        let image = match &keycap.pressed {
+            Some(pressed) if is_pressed => pressed,
+            _ => &keycap.normal,
+        };
+        image.paint(ui.painter(), rect, tint);
+        ui.painter()
+            .galley(rect.center() - galley.size() / 2.0, galley, text_color);
+        Some(rect.size())
+    }
+
+    /// Draws the mouse from images in place of `mouse::draw_mouse`, with the
+    /// same scroll indicator. Returns false, drawing nothing, when the skin has
+    /// no mouse images.
+    pub fn draw_mouse(
+        &self,
+        ui: &mut egui::Ui,
+        mouse_buttons: &[bool; 5],
+        wheel: &mouse::WheelState,
+    ) -> bool {
+        let Some(mouse) = &self.mouse else {
+            return false;
+        };
+
+        let (_id, full_rect) = ui.allocate_space(egui::vec2(64.0 + mouse::SCROLL_WIDTH, 64.0));
+        let (rect, scroll_rect) = full_rect.split_left_right_at_x(full_rect.left() + 64.0);
+        let body_size = mouse.body.size();
+        let scale = (rect.width() / body_size.x).min(rect.height() / body_size.y);
+        let body_rect = egui::Rect::from_center_size(rect.center(), body_size * scale);
+
+        let painter = ui.painter_at(full_rect);
+        mouse.body.paint(&painter, body_rect, egui::Color32::WHITE);
+        for (button, image) in mouse.buttons.iter().enumerate() {
+            if let Some(image) = image {
+                if mouse_buttons[button] {
+                    image.paint(&painter, body_rect, egui::Color32::WHITE);
+                }
+            }
+        }
+        mouse::draw_scroll(&painter, scroll_rect, wheel, ui.visuals().text_color());
+        true
+    }
+
+    /// Fills the window background with the skin's image, returning false
+    /// when the skin has none.
+    pub fn draw_background(&self, ui: &egui::Ui, rect: egui::Rect) -> bool {
+        let Some(background) = &self.background else {
+            return false;
+        };
+        background.paint(&ui.painter_at(rect), rect, egui::Color32::WHITE);
+        true
+    }
+}
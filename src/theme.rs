@@ -0,0 +1,322 @@
+// Themes: the font, text sizes and colors of the overlay and its window.
+//
+// Light, dark and high contrast are built in, and "System" switches between
+// light and dark with the OS setting. A custom theme is a `.toml` file under
+// `themes/` next to the executable that starts from a preset and overrides
+// any of its values:
+//
+//     base = "dark"                  # "light", "dark" or "high-contrast"
+//     font = "Inter-Bold.otf"        # TTF or OTF, relative to `themes/`
+//     font_size = 56.0
+//     history_font_size = 28.0
+//     gesture_font_size = 14.0
+//     text_color = "#ffffff"
+//     outline_color = "#00000040"
+//     outline_width = 2.0
+//     background = "#1b1b1bf0"
+//     corner_radius = 4.0
+//
+// Without `base` the theme follows the system's light or dark mode. The font
+// is used for overlay text only, so the settings window stays readable.
+
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use eframe::egui;
+use serde::Deserialize;
+
+use crate::locale;
+
+// Font family of the overlay text: the theme font, then egui's own
+const FONT_FAMILY: &str = "overlay";
+const THEME_FONT: &str = "theme";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThemePreset {
+    Light,
+    Dark,
+    HighContrast,
+}
+
+impl ThemePreset {
+    pub const ALL: [ThemePreset; 3] = [
+        ThemePreset::Light,
+        ThemePreset::Dark,
+        ThemePreset::HighContrast,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ThemePreset::Light => "Light",
+            ThemePreset::Dark => "Dark",
+            ThemePreset::HighContrast => "High contrast",
+        }
+    }
+
+    /// Light or dark to match the OS, dark when it doesn't say.
+    pub fn for_system(system: Option<egui::Theme>) -> Self {
+        match system {
+            Some(egui::Theme::Light) => ThemePreset::Light,
+            _ => ThemePreset::Dark,
+        }
+    }
+
+    fn visuals(self) -> egui::Visuals {
+        match self {
+            ThemePreset::Light => egui::Visuals::light(),
+            ThemePreset::Dark => egui::Visuals::dark(),
+            ThemePreset::HighContrast => {
+                let mut visuals = egui::Visuals::dark();
+                let white = egui::Stroke::new(1.0, egui::Color32::WHITE);
+                visuals.override_text_color = Some(egui::Color32::WHITE);
+                visuals.panel_fill = egui::Color32::BLACK;
+                visuals.window_fill = egui::Color32::BLACK;
+                visuals.window_stroke = white;
+                visuals.extreme_bg_color = egui::Color32::BLACK;
+                visuals.faint_bg_color = egui::Color32::from_gray(24);
+                for widget in [
+                    &mut visuals.widgets.noninteractive,
+                    &mut visuals.widgets.inactive,
+                    &mut visuals.widgets.hovered,
+                    &mut visuals.widgets.active,
+                    &mut visuals.widgets.open,
+                ] {
+                    widget.bg_stroke = white;
+                    widget.fg_stroke.color = egui::Color32::WHITE;
+                }
+                visuals.widgets.inactive.bg_fill = egui::Color32::BLACK;
+                visuals.widgets.inactive.weak_bg_fill = egui::Color32::BLACK;
+                visuals.widgets.active.bg_fill = egui::Color32::YELLOW;
+                visuals.widgets.active.fg_stroke.color = egui::Color32::BLACK;
+                visuals.selection.bg_fill = egui::Color32::YELLOW;
+                visuals.selection.stroke.color = egui::Color32::BLACK;
+                visuals
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ThemeError {
+    Io(PathBuf, io::Error),
+    Manifest(toml::de::Error),
+    Color(String),
+    Font(PathBuf),
+}
+
+impl fmt::Display for ThemeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThemeError::Io(path, err) => write!(f, "cannot read {}: {err}", path.display()),
+            ThemeError::Manifest(err) => write!(f, "invalid theme: {err}"),
+            ThemeError::Color(text) => write!(f, "invalid color \"{text}\""),
+            ThemeError::Font(path) => write!(f, "{} is not a TTF or OTF font", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for ThemeError {}
+
+#[derive(Deserialize)]
+struct Manifest {
+    base: Option<ThemePreset>,
+    font: Option<PathBuf>,
+    font_size: Option<f32>,
+    history_font_size: Option<f32>,
+    gesture_font_size: Option<f32>,
+    text_color: Option<String>,
+    outline_color: Option<String>,
+    outline_width: Option<f32>,
+    background: Option<String>,
+    corner_radius: Option<f32>,
+}
+
+pub struct Theme {
+    visuals: egui::Visuals,
+    font: Option<egui::FontData>,
+    /// Size of the current combo and the ticker.
+    pub font_size: f32,
+    pub history_font_size: f32,
+    pub gesture_font_size: f32,
+    pub text_color: egui::Color32,
+    pub outline_color: egui::Color32,
+    pub outline_width: f32,
+    /// Fill of the window behind the overlay while it isn't click-through.
+    pub background: egui::Color32,
+    pub corner_radius: f32,
+}
+
+/// Font and colors of one piece of overlay text, already faded.
+pub struct TextStyle {
+    pub font: egui::FontId,
+    pub color: egui::Color32,
+    /// Outline color and thickness.
+    pub outline: Option<(egui::Color32, f32)>,
+}
+
+/// Directory holding the custom themes, next to the executable.
+pub fn themes_dir() -> Option<PathBuf> {
+    Some(std::env::current_exe().ok()?.parent()?.join("themes"))
+}
+
+/// Names of the custom themes found in `themes_dir`, sorted.
+pub fn available_themes() -> Vec<String> {
+    let Some(entries) = themes_dir().and_then(|dir| fs::read_dir(dir).ok()) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+        .filter_map(|path| Some(path.file_stem()?.to_str()?.to_string()))
+        .collect();
+    names.sort();
+    names
+}
+
+/// Font id for overlay text, drawn in the theme font when there is one.
+pub fn overlay_font(size: f32) -> egui::FontId {
+    egui::FontId::new(size, egui::FontFamily::Name(FONT_FAMILY.into()))
+}
+
+/// Sets up egui's fonts with the Hangul fallback and the overlay family, which
+/// puts `font` ahead of the proportional fonts. Overlay text needs the family
+/// from the first frame, so this also runs before any theme is loaded.
+pub fn install_fonts(ctx: &egui::Context, font: Option<egui::FontData>) {
+    let mut fonts = egui::FontDefinitions::default();
+    locale::add_fallback_fonts(&mut fonts);
+
+    let mut overlay = fonts
+        .families
+        .get(&egui::FontFamily::Proportional)
+        .cloned()
+        .unwrap_or_default();
+    if let Some(font) = font {
+        fonts.font_data.insert(THEME_FONT.to_owned(), font.into());
+        overlay.insert(0, THEME_FONT.to_owned());
+    }
+    fonts
+        .families
+        .insert(egui::FontFamily::Name(FONT_FAMILY.into()), overlay);
+    ctx.set_fonts(fonts);
+}
+
+fn parse_color(text: &str) -> Result<egui::Color32, ThemeError> {
+    egui::Color32::from_hex(text).map_err(|_| ThemeError::Color(text.to_string()))
+}
+
+// egui panics on font files it can't parse, so they are checked up front
+fn load_font(path: &Path) -> Result<egui::FontData, ThemeError> {
+    let bytes = fs::read(path).map_err(|err| ThemeError::Io(path.to_path_buf(), err))?;
+    let is_valid = ab_glyph::FontRef::try_from_slice(&bytes)
+        .is_ok_and(|font| ab_glyph::Font::units_per_em(&font).is_some());
+    if !is_valid {
+        return Err(ThemeError::Font(path.to_path_buf()));
+    }
+    Ok(egui::FontData::from_owned(bytes))
+}
+
+impl Theme {
+    pub fn preset(preset: ThemePreset) -> Self {
+        let visuals = preset.visuals();
+        let (text_color, outline_color, outline_width) = match preset {
+            ThemePreset::HighContrast => (egui::Color32::WHITE, egui::Color32::BLACK, 3.0),
+            _ => (
+                visuals.text_color(),
+                egui::Color32::from_black_alpha(64),
+                2.0,
+            ),
+        };
+        Self {
+            font: None,
+            font_size: 56.0,
+            history_font_size: 28.0,
+            gesture_font_size: 14.0,
+            text_color,
+            outline_color,
+            outline_width,
+            background: visuals.window_fill(),
+            corner_radius: 4.0,
+            visuals,
+        }
+    }
+
+    /// Loads the custom theme `name` from `themes_dir`. `system` is the OS
+    /// theme, used when the file names no base preset.
+    pub fn load(name: &str, system: Option<egui::Theme>) -> Result<Self, ThemeError> {
+        let dir = themes_dir().unwrap_or_default();
+        let path = dir.join(format!("{name}.toml"));
+        let text = fs::read_to_string(&path).map_err(|err| ThemeError::Io(path, err))?;
+        let manifest: Manifest = toml::from_str(&text).map_err(ThemeError::Manifest)?;
+
+        let base = manifest
+            .base
+            .unwrap_or_else(|| ThemePreset::for_system(system));
+        let mut theme = Self::preset(base);
+        if let Some(font) = &manifest.font {
+            theme.font = Some(load_font(&dir.join(font))?);
+        }
+        // Each size with the smallest value that still draws
+        let sizes = [
+            (&mut theme.font_size, manifest.font_size, 1.0),
+            (
+                &mut theme.history_font_size,
+                manifest.history_font_size,
+                1.0,
+            ),
+            (
+                &mut theme.gesture_font_size,
+                manifest.gesture_font_size,
+                1.0,
+            ),
+            (&mut theme.outline_width, manifest.outline_width, 0.0),
+            (&mut theme.corner_radius, manifest.corner_radius, 0.0),
+        ];
+        for (size, value, min) in sizes {
+            if let Some(value) = value {
+                *size = value.max(min);
+            }
+        }
+        let colors = [
+            (&mut theme.text_color, &manifest.text_color),
+            (&mut theme.outline_color, &manifest.outline_color),
+            (&mut theme.background, &manifest.background),
+        ];
+        for (color, text) in colors {
+            if let Some(text) = text {
+                *color = parse_color(text)?;
+            }
+        }
+        Ok(theme)
+    }
+
+    /// Switches egui to the theme's visuals and fonts.
+    pub fn apply(&self, ctx: &egui::Context) {
+        let theme = if self.visuals.dark_mode {
+            egui::Theme::Dark
+        } else {
+            egui::Theme::Light
+        };
+        ctx.set_visuals_of(theme, self.visuals.clone());
+        ctx.set_theme(theme);
+        install_fonts(ctx, self.font.clone());
+    }
+
+    /// Style of overlay text at `size`, faded to `alpha`.
+    pub fn text_style(&self, size: f32, alpha: u8, is_outline: bool) -> TextStyle {
+        let opacity = alpha as f32 / 255.0;
+        TextStyle {
+            font: overlay_font(size),
+            color: self.text_color.gamma_multiply(opacity),
+            outline: is_outline.then(|| {
+                (
+                    self.outline_color.gamma_multiply(opacity),
+                    self.outline_width,
+                )
+            }),
+        }
+    }
+}
@@ -0,0 +1,196 @@
+// Typed-text ticker: keystrokes accumulate into running text, Backspace
+// removes characters, and shortcuts are kept as separate chips in between.
+
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+// Characters kept before the oldest text is dropped
+const MAX_CHARS: usize = 256;
+
+// Typing after this much idle time starts a fresh line
+const IDLE_RESET: Duration = Duration::from_secs(5);
+
+pub enum TickerItem {
+    Text(String),
+    Chip(String),
+}
+
+pub struct Ticker {
+    items: VecDeque<TickerItem>,
+    pub updated: Instant,
+}
+
+impl Default for Ticker {
+    fn default() -> Self {
+        Self {
+            items: VecDeque::new(),
+            updated: Instant::now(),
+        }
+    }
+}
+
+impl Ticker {
+    pub fn items(&self) -> impl DoubleEndedIterator<Item = &TickerItem> {
+        self.items.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn type_text(&mut self, text: &str, time: Instant) {
+        self.touch(time);
+        match self.items.back_mut() {
+            Some(TickerItem::Text(current)) => current.push_str(text),
+            _ => self.items.push_back(TickerItem::Text(text.to_string())),
+        }
+        self.trim();
+    }
+
+    /// Removes the last typed character. Chips are never deleted, since the
+    /// shortcut they stand for has already happened.
+    pub fn backspace(&mut self, time: Instant) {
+        self.touch(time);
+        if let Some(TickerItem::Text(current)) = self.items.back_mut() {
+            current.pop();
+            if current.is_empty() {
+                self.items.pop_back();
+            }
+        }
+    }
+
+    pub fn push_chip(&mut self, label: String, time: Instant) {
+        self.touch(time);
+        self.items.push_back(TickerItem::Chip(label));
+        self.trim();
+    }
+
+    fn touch(&mut self, time: Instant) {
+        if time.duration_since(self.updated) > IDLE_RESET {
+            self.items.clear();
+        }
+        self.updated = time;
+    }
+
+    fn trim(&mut self) {
+        let mut total: usize = self.items.iter().map(item_len).sum();
+        while total > MAX_CHARS {
+            let Some(front) = self.items.front_mut() else {
+                break;
+            };
+            match front {
+                TickerItem::Text(text) if text.chars().count() > total - MAX_CHARS => {
+                    let excess = total - MAX_CHARS;
+                    *text = text.chars().skip(excess).collect();
+                    total = MAX_CHARS;
+                }
+                _ => {
+                    total -= item_len(front);
+                    self.items.pop_front();
+                }
+            }
+        }
+    }
+}
+
+fn item_len(item: &TickerItem) -> usize {
+    match item {
+        TickerItem::Text(text) => text.chars().count(),
+        TickerItem::Chip(label) => label.chars().count(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Text items as typed, chips in brackets
+    fn contents(ticker: &Ticker) -> Vec<String> {
+        ticker
+            .items()
+            .map(|item| match item {
+                TickerItem::Text(text) => text.clone(),
+                TickerItem::Chip(label) => format!("[{label}]"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn keeps_chips_between_runs_of_text() {
+        let mut ticker = Ticker::default();
+        let now = ticker.updated;
+        ticker.type_text("he", now);
+        ticker.type_text("y", now);
+        ticker.push_chip("Ctrl + S".to_string(), now);
+        ticker.type_text("ok", now);
+        assert_eq!(contents(&ticker), ["hey", "[Ctrl + S]", "ok"]);
+    }
+
+    #[test]
+    fn backspace_deletes_text_but_not_chips() {
+        let mut ticker = Ticker::default();
+        let now = ticker.updated;
+        ticker.type_text("añ", now);
+        ticker.backspace(now);
+        assert_eq!(contents(&ticker), ["a"]);
+
+        ticker.push_chip("Ctrl + C".to_string(), now);
+        ticker.backspace(now);
+        assert_eq!(contents(&ticker), ["a", "[Ctrl + C]"]);
+
+        ticker.type_text("b", now);
+        ticker.backspace(now);
+        // The emptied text is removed rather than left between chips
+        assert_eq!(contents(&ticker), ["a", "[Ctrl + C]"]);
+    }
+
+    #[test]
+    fn trims_oldest_characters_past_the_limit() {
+        let mut ticker = Ticker::default();
+        let now = ticker.updated;
+        ticker.type_text(&"a".repeat(MAX_CHARS - 2), now);
+        ticker.type_text("bcd", now);
+        let text = &contents(&ticker)[0];
+        assert_eq!(text.chars().count(), MAX_CHARS);
+        assert!(text.starts_with('a') && text.ends_with("bcd"));
+
+        // Multi-byte characters count once
+        ticker.type_text("éé", now);
+        assert_eq!(contents(&ticker)[0].chars().count(), MAX_CHARS);
+    }
+
+    #[test]
+    fn trims_whole_chips_at_the_front() {
+        let mut ticker = Ticker::default();
+        let now = ticker.updated;
+        ticker.push_chip("Ctrl + Z".to_string(), now);
+        ticker.type_text(&"a".repeat(MAX_CHARS - 8), now);
+        assert_eq!(contents(&ticker).len(), 2);
+
+        // One more character doesn't fit, and a chip can't be cut short
+        ticker.type_text("b", now);
+        let contents = contents(&ticker);
+        assert_eq!(contents.len(), 1);
+        assert_eq!(contents[0].chars().count(), MAX_CHARS - 7);
+    }
+
+    #[test]
+    fn starts_over_after_idling() {
+        let mut ticker = Ticker::default();
+        let start = ticker.updated;
+        ticker.type_text("old", start);
+        ticker.type_text("er", start + IDLE_RESET);
+        assert_eq!(contents(&ticker), ["older"]);
+
+        let later = start + IDLE_RESET * 2 + Duration::from_millis(1);
+        ticker.type_text("new", later);
+        assert_eq!(contents(&ticker), ["new"]);
+        assert_eq!(ticker.updated, later);
+
+        // Backspace after idling leaves nothing to delete
+        ticker.backspace(later + IDLE_RESET * 2);
+        assert!(ticker.is_empty());
+    }
+}